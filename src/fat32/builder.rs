@@ -0,0 +1,67 @@
+use super::{Clock, Disk, Error, Fat32, MountOptions};
+
+/// Configuration du montage d'un volume, pour les cas où `Fat32::mount` ne suffit pas.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Fat32Builder {
+    options: MountOptions,
+}
+
+impl Fat32Builder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.options.read_only = read_only;
+        self
+    }
+
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.options.strict = strict;
+        self
+    }
+
+    pub fn fat_cache(mut self, fat_cache: bool) -> Self {
+        self.options.fat_cache = fat_cache;
+        self
+    }
+
+    pub fn deferred_fat_mirror(mut self, deferred_fat_mirror: bool) -> Self {
+        self.options.deferred_fat_mirror = deferred_fat_mirror;
+        self
+    }
+
+    pub fn max_path_depth(mut self, max_path_depth: u16) -> Self {
+        self.options.max_path_depth = max_path_depth;
+        self
+    }
+
+    pub fn max_read_size(mut self, max_read_size: u32) -> Self {
+        self.options.max_read_size = max_read_size;
+        self
+    }
+
+    pub fn end_of_chain(mut self, end_of_chain: u32) -> Self {
+        self.options.end_of_chain = end_of_chain;
+        self
+    }
+
+    pub fn partition_start(mut self, partition_start: u32) -> Self {
+        self.options.partition_start = Some(partition_start);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: &'static dyn Clock) -> Self {
+        self.options.clock = Some(clock);
+        self
+    }
+
+    /// Options équivalentes, pour `Fat32::mount_with_options`.
+    pub fn options(&self) -> MountOptions {
+        self.options
+    }
+
+    pub fn mount<D: Disk>(self, disk: D) -> Result<Fat32<D>, Error> {
+        Fat32::mount_with_options(disk, self.options)
+    }
+}
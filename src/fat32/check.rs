@@ -0,0 +1,72 @@
+use super::{Disk, Error, Fat32, FileInfo};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+/// Résultat de `Fat32::check`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FsCheckReport {
+    /// Entrées ignorées par les listages à cause d'une combinaison d'attributs invalide.
+    pub invalid_entries: u32,
+    /// Paires d'entrées distinctes qui commencent au même cluster : le chemin vu en premier,
+    /// puis celui qui le réclame aussi.
+    pub cross_linked: Vec<(String, String)>,
+}
+
+impl<D: Disk> Fat32<D> {
+    /// Parcourt toute l'arborescence et signale les incohérences rencontrées, sans rien
+    /// modifier. Comme `walk_iter`, échoue avec `Error::PathTooDeep` plutôt que de laisser
+    /// une partie de l'arborescence sans vérification.
+    pub fn check(&self) -> Result<FsCheckReport, Error> {
+        let mut report = FsCheckReport::default();
+        let max_depth = self.options.max_path_depth as usize;
+        let mut claimed = BTreeMap::new();
+        claimed.insert(self.info.root_cluster, String::from("/"));
+        let mut pending = vec![(self.info.root_cluster, String::new(), 0)];
+
+        while let Some((cluster, path, depth)) = pending.pop() {
+            // Les enfants de ce répertoire dépasseraient la profondeur autorisée
+            if depth >= max_depth {
+                return Err(Error::PathTooDeep);
+            }
+            self.scan_directory(cluster, |entry, _| {
+                if entry.name[0] == 0x00 {
+                    return Some(());
+                }
+                if entry.name[0] == 0xE5 {
+                    return None;
+                }
+                if entry.has_invalid_attributes() {
+                    report.invalid_entries += 1;
+                    return None;
+                }
+                if entry.is_lfn_or_volume_label() {
+                    return None;
+                }
+
+                let info = FileInfo::from_entry(entry, self.info.root_cluster);
+                if info.name == "." || info.name == ".." {
+                    return None;
+                }
+                let entry_path = format!("{}/{}", path, info.name);
+                if info.start_cluster >= 2 {
+                    if let Some(first) = claimed.get(&info.start_cluster) {
+                        report
+                            .cross_linked
+                            .push((first.clone(), entry_path.clone()));
+                        // Ne pas redescendre dans un répertoire déjà visité
+                        return None;
+                    }
+                    claimed.insert(info.start_cluster, entry_path.clone());
+                }
+                if info.is_directory {
+                    pending.push((info.start_cluster, entry_path, depth + 1));
+                }
+                None
+            })?;
+        }
+
+        Ok(report)
+    }
+}
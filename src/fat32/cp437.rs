@@ -0,0 +1,63 @@
+/// Caractères Unicode des octets 0x80 à 0xFF de la page de code OEM 437, dans laquelle sont
+/// stockés les noms courts et le nom du volume. La moitié basse coïncide avec ASCII.
+const HIGH_HALF: [char; 128] = [
+    '\u{00C7}', '\u{00FC}', '\u{00E9}', '\u{00E2}', '\u{00E4}', '\u{00E0}', '\u{00E5}', '\u{00E7}',
+    '\u{00EA}', '\u{00EB}', '\u{00E8}', '\u{00EF}', '\u{00EE}', '\u{00EC}', '\u{00C4}', '\u{00C5}',
+    '\u{00C9}', '\u{00E6}', '\u{00C6}', '\u{00F4}', '\u{00F6}', '\u{00F2}', '\u{00FB}', '\u{00F9}',
+    '\u{00FF}', '\u{00D6}', '\u{00DC}', '\u{00A2}', '\u{00A3}', '\u{00A5}', '\u{20A7}', '\u{0192}',
+    '\u{00E1}', '\u{00ED}', '\u{00F3}', '\u{00FA}', '\u{00F1}', '\u{00D1}', '\u{00AA}', '\u{00BA}',
+    '\u{00BF}', '\u{2310}', '\u{00AC}', '\u{00BD}', '\u{00BC}', '\u{00A1}', '\u{00AB}', '\u{00BB}',
+    '\u{2591}', '\u{2592}', '\u{2593}', '\u{2502}', '\u{2524}', '\u{2561}', '\u{2562}', '\u{2556}',
+    '\u{2555}', '\u{2563}', '\u{2551}', '\u{2557}', '\u{255D}', '\u{255C}', '\u{255B}', '\u{2510}',
+    '\u{2514}', '\u{2534}', '\u{252C}', '\u{251C}', '\u{2500}', '\u{253C}', '\u{255E}', '\u{255F}',
+    '\u{255A}', '\u{2554}', '\u{2569}', '\u{2566}', '\u{2560}', '\u{2550}', '\u{256C}', '\u{2567}',
+    '\u{2568}', '\u{2564}', '\u{2565}', '\u{2559}', '\u{2558}', '\u{2552}', '\u{2553}', '\u{256B}',
+    '\u{256A}', '\u{2518}', '\u{250C}', '\u{2588}', '\u{2584}', '\u{258C}', '\u{2590}', '\u{2580}',
+    '\u{03B1}', '\u{00DF}', '\u{0393}', '\u{03C0}', '\u{03A3}', '\u{03C3}', '\u{00B5}', '\u{03C4}',
+    '\u{03A6}', '\u{0398}', '\u{03A9}', '\u{03B4}', '\u{221E}', '\u{03C6}', '\u{03B5}', '\u{2229}',
+    '\u{2261}', '\u{00B1}', '\u{2265}', '\u{2264}', '\u{2320}', '\u{2321}', '\u{00F7}', '\u{2248}',
+    '\u{00B0}', '\u{2219}', '\u{00B7}', '\u{221A}', '\u{207F}', '\u{00B2}', '\u{25A0}', '\u{00A0}',
+];
+
+/// Décode un octet CP437.
+pub(super) fn decode(byte: u8) -> char {
+    match byte {
+        0x00..0x80 => char::from(byte),
+        _ => HIGH_HALF[byte as usize - 0x80],
+    }
+}
+
+/// Passe un octet CP437 en majuscule avec la table de DOS : les minuscules accentuées
+/// prennent la majuscule de la page de code quand elle existe (é → É), sinon la lettre
+/// ASCII sans accent (â → A). Les autres octets sont inchangés.
+pub(super) fn to_upper(byte: u8) -> u8 {
+    match byte {
+        b'a'..=b'z' => byte.to_ascii_uppercase(),
+        0x81 => 0x9A,
+        0x82 => 0x90,
+        0x83 | 0x85 | 0xA0 => b'A',
+        0x84 => 0x8E,
+        0x86 => 0x8F,
+        0x87 => 0x80,
+        0x88..=0x8A => b'E',
+        0x8B..=0x8D | 0xA1 => b'I',
+        0x91 => 0x92,
+        0x93 | 0x95 | 0xA2 => b'O',
+        0x94 => 0x99,
+        0x96 | 0x97 | 0xA3 => b'U',
+        0x98 => b'Y',
+        0xA4 => 0xA5,
+        _ => byte,
+    }
+}
+
+/// Encode un caractère en CP437, s'il y figure.
+pub(super) fn encode(c: char) -> Option<u8> {
+    if c.is_ascii() {
+        return Some(c as u8);
+    }
+    HIGH_HALF
+        .iter()
+        .position(|&high| high == c)
+        .map(|i| 0x80 + i as u8)
+}
@@ -0,0 +1,57 @@
+use super::{Disk, Error, Fat32, FileInfo};
+use alloc::vec::Vec;
+
+/// Répertoire ouvert sur un volume, désigné par son premier cluster.
+pub struct Dir<'a, D: Disk> {
+    fs: &'a Fat32<D>,
+    cluster: u32,
+}
+
+impl<'a, D: Disk> Dir<'a, D> {
+    pub fn cluster(&self) -> u32 {
+        self.cluster
+    }
+
+    /// Entrées du répertoire, `.` et `..` compris.
+    pub fn entries(&self) -> Result<Vec<FileInfo>, Error> {
+        self.fs.read_directory(self.cluster)
+    }
+
+    /// Cherche une entrée par son nom, sans tenir compte de la casse.
+    pub fn find(&self, name: &str) -> Result<FileInfo, Error> {
+        self.fs
+            .find_named(self.cluster, name)?
+            .map(|(info, _)| info)
+            .ok_or(Error::FileNotFound)
+    }
+
+    /// Ouvre le sous-répertoire `name`.
+    pub fn open_dir(&self, name: &str) -> Result<Dir<'a, D>, Error> {
+        let info = self.find(name)?;
+        Ok(Dir {
+            fs: self.fs,
+            cluster: self.fs.subdirectory_cluster(&info)?,
+        })
+    }
+}
+
+impl<D: Disk> Fat32<D> {
+    pub fn root_cluster(&self) -> u32 {
+        self.info.root_cluster
+    }
+
+    pub fn root_dir(&self) -> Dir<'_, D> {
+        Dir {
+            fs: self,
+            cluster: self.info.root_cluster,
+        }
+    }
+
+    /// Ouvre le répertoire désigné par `path`.
+    pub fn open_dir(&self, path: &str) -> Result<Dir<'_, D>, Error> {
+        Ok(Dir {
+            fs: self,
+            cluster: self.directory_cluster(path)?,
+        })
+    }
+}
@@ -0,0 +1,231 @@
+use super::{Disk, EntryLocation, Error, Fat32, SECTOR_SIZE, is_end_of_chain};
+use core::fmt;
+
+/// Fichier ouvert en écriture par `Fat32::open_mut` ou `Fat32::open_append`. Chaque
+/// écriture part de la position courante, qu'elle fait avancer ; le cluster courant est
+/// retenu pour ne pas reparcourir la chaîne. La taille et la date de modification sont
+/// tenues en mémoire et écrites dans l'entrée par `flush` ou à l'abandon du fichier.
+///
+/// Les écritures formatées (`write!`) sont d'abord accumulées jusqu'à la fin du secteur
+/// courant ; `flush`, `write`, `seek` et l'abandon du fichier écrivent ce qui reste.
+pub struct FileMut<'a, D: Disk> {
+    fs: &'a mut Fat32<D>,
+    location: EntryLocation,
+    first_cluster: u32,
+    size: u32,
+    /// Indice dans la chaîne et numéro du dernier cluster atteint.
+    cursor: Option<(u32, u32)>,
+    /// La taille ou la date de l'entrée n'ont pas encore été écrites.
+    dirty: bool,
+    /// Position courante, octets en attente dans `buffer` compris.
+    position: u32,
+    buffer: [u8; SECTOR_SIZE],
+    buffered: usize,
+}
+
+impl<D: Disk> FileMut<'_, D> {
+    pub fn position(&self) -> u32 {
+        self.position
+    }
+
+    /// Taille actuelle du fichier, octets en attente compris.
+    pub fn len(&self) -> u32 {
+        self.size.max(self.position)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Déplace la position courante, au plus jusqu'à la fin du fichier.
+    pub fn seek(&mut self, position: u32) -> Result<(), Error> {
+        self.write_buffer()?;
+        if position > self.len() {
+            return Err(Error::OutOfRange);
+        }
+        self.position = position;
+        Ok(())
+    }
+
+    /// Écrit `buf` à la position courante, en allongeant le fichier si nécessaire, et
+    /// renvoie le nombre d'octets écrits.
+    pub fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.write_buffer()?;
+        let len = u32::try_from(buf.len()).map_err(|_| Error::FileTooLarge)?;
+        let end = self.position.checked_add(len).ok_or(Error::FileTooLarge)?;
+        self.write_bytes(self.position, buf)?;
+        self.position = end;
+        Ok(buf.len())
+    }
+
+    /// Écrit les octets en attente et l'entrée du fichier, puis les informations du volume
+    /// tenues en mémoire.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.write_buffer()?;
+        self.write_entry()?;
+        self.fs.flush()
+    }
+
+    /// Accumule `bytes` dans le secteur courant, écrit dès qu'il est plein.
+    fn buffer_bytes(&mut self, mut bytes: &[u8]) -> Result<(), Error> {
+        let len = u32::try_from(bytes.len()).map_err(|_| Error::FileTooLarge)?;
+        self.position.checked_add(len).ok_or(Error::FileTooLarge)?;
+
+        while !bytes.is_empty() {
+            let start = self.position as usize - self.buffered;
+            let capacity = SECTOR_SIZE - start % SECTOR_SIZE;
+            let n = bytes.len().min(capacity - self.buffered);
+            self.buffer[self.buffered..self.buffered + n].copy_from_slice(&bytes[..n]);
+            self.buffered += n;
+            self.position += n as u32;
+            bytes = &bytes[n..];
+            if self.buffered == capacity {
+                self.write_buffer()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_buffer(&mut self) -> Result<(), Error> {
+        if self.buffered == 0 {
+            return Ok(());
+        }
+        let start = self.position - self.buffered as u32;
+        let buffer = self.buffer;
+        self.write_bytes(start, &buffer[..self.buffered])?;
+        self.buffered = 0;
+        Ok(())
+    }
+
+    /// Écrit `data` à l'octet `offset` (au plus la taille actuelle), cluster par cluster.
+    fn write_bytes(&mut self, offset: u32, mut data: &[u8]) -> Result<(), Error> {
+        let cluster_size = self.fs.cluster_size();
+        let mut offset = offset;
+        while !data.is_empty() {
+            let cluster = self.cluster_for(offset / cluster_size)?;
+            let within = offset % cluster_size;
+            let n = data.len().min((cluster_size - within) as usize);
+            self.fs.write_chain_at(cluster, within, &data[..n])?;
+            offset += n as u32;
+            data = &data[n..];
+            if offset > self.size {
+                self.size = offset;
+            }
+            self.dirty = true;
+        }
+        Ok(())
+    }
+
+    /// Renvoie le cluster d'indice `index` de la chaîne, en partant du curseur s'il est
+    /// avant, et en allongeant la chaîne au besoin.
+    fn cluster_for(&mut self, index: u32) -> Result<u32, Error> {
+        if self.first_cluster < 2 {
+            self.attach_first_cluster()?;
+        }
+        let (mut i, mut cluster) = match self.cursor {
+            Some((i, cluster)) if i <= index => (i, cluster),
+            _ => (0, self.first_cluster),
+        };
+        while i < index {
+            let next = self.fs.get_fat_entry(cluster)?;
+            cluster = if is_end_of_chain(next) {
+                let new = self.fs.allocate_cluster()?;
+                if let Err(e) = self.fs.set_fat_entry(cluster, new) {
+                    self.fs.release_chain(new);
+                    return Err(e);
+                }
+                new
+            } else {
+                next
+            };
+            i += 1;
+        }
+        self.cursor = Some((index, cluster));
+        Ok(cluster)
+    }
+
+    /// Réserve le premier cluster d'un fichier vide et l'inscrit aussitôt dans l'entrée,
+    /// pour que la chaîne ne soit pas perdue si l'entrée n'est jamais écrite.
+    fn attach_first_cluster(&mut self) -> Result<(), Error> {
+        let cluster = self.fs.allocate_cluster()?;
+        let result = self.fs.modify_entry(self.location, |entry| {
+            entry.first_cluster_high = (cluster >> 16) as u16;
+            entry.first_cluster_low = cluster as u16;
+        });
+        if let Err(e) = result {
+            self.fs.release_chain(cluster);
+            return Err(e);
+        }
+        self.first_cluster = cluster;
+        self.cursor = None;
+        Ok(())
+    }
+
+    /// Écrit la taille et la date de modification dans l'entrée si elles ont changé.
+    fn write_entry(&mut self) -> Result<(), Error> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let size = self.size;
+        let now = self.fs.now();
+        self.fs.modify_entry(self.location, |entry| {
+            entry.file_size = size;
+            if let Some(now) = now {
+                entry.write_time = now.fat_time();
+                entry.write_date = now.fat_date();
+            }
+        })?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+impl<D: Disk> fmt::Write for FileMut<'_, D> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.buffer_bytes(s.as_bytes()).map_err(|_| fmt::Error)
+    }
+}
+
+/// Les octets en attente puis l'entrée sont écrits ; une erreur à ce moment est perdue,
+/// `flush` permet de la récupérer.
+impl<D: Disk> Drop for FileMut<'_, D> {
+    fn drop(&mut self) {
+        if self.write_buffer().is_ok() {
+            let _ = self.write_entry();
+        }
+    }
+}
+
+impl<D: Disk> Fat32<D> {
+    /// Ouvre le fichier existant `path` en écriture, positionné au début.
+    pub fn open_mut(&mut self, path: &str) -> Result<FileMut<'_, D>, Error> {
+        self.open_file_mut(path, false)
+    }
+
+    /// Ouvre le fichier existant `path` en écriture, positionné à la fin : les écritures
+    /// suivantes, `write!` compris, s'ajoutent au contenu.
+    pub fn open_append(&mut self, path: &str) -> Result<FileMut<'_, D>, Error> {
+        self.open_file_mut(path, true)
+    }
+
+    fn open_file_mut(&mut self, path: &str, at_end: bool) -> Result<FileMut<'_, D>, Error> {
+        if self.options.read_only {
+            return Err(Error::ReadOnly);
+        }
+        let (info, location) = self.locate(path)?;
+        if info.is_directory {
+            return Err(Error::IsADirectory);
+        }
+        Ok(FileMut {
+            fs: self,
+            location,
+            first_cluster: info.start_cluster,
+            size: info.size,
+            cursor: None,
+            dirty: false,
+            position: if at_end { info.size } else { 0 },
+            buffer: [0; SECTOR_SIZE],
+            buffered: 0,
+        })
+    }
+}
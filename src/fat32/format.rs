@@ -0,0 +1,280 @@
+use super::write::volume_label_entry;
+use super::{
+    BootSector, Disk, Error, FAT32_ENTRY_MASK, FS_INFO_LEAD_SIGNATURE, FS_INFO_STRUCT_SIGNATURE,
+    FS_INFO_TRAIL_SIGNATURE, Fat32, FsInfoSector, SECTOR_SIZE, cast_slice_to_struct,
+    cast_slice_to_struct_mut, io_at, is_end_of_chain, name,
+};
+
+const FS_INFO_SECTOR: u16 = 1;
+const BACKUP_BOOT_SECTOR: u16 = 6;
+const ROOT_CLUSTER: u32 = 2;
+const MEDIA_DESCRIPTOR: u8 = 0xF8;
+/// Bornes du nombre de clusters d'un volume FAT32.
+const MIN_CLUSTERS: u32 = 65525;
+const MAX_CLUSTERS: u32 = 0x0FFFFFF5;
+
+/// Taille de cluster recommandée par Microsoft selon le nombre de secteurs de 512 octets ;
+/// en dessous de 66600 secteurs, le volume est trop petit pour FAT32.
+const CLUSTER_SIZE_TABLE: [(u32, u32); 6] = [
+    (66_600, 0),
+    (532_480, 1),
+    (16_777_216, 8),
+    (33_554_432, 16),
+    (67_108_864, 32),
+    (u32::MAX, 64),
+];
+
+/// Paramètres de `Fat32::format`.
+#[derive(Debug, Clone, Copy)]
+pub struct FormatOptions<'a> {
+    pub volume_label: Option<&'a str>,
+    pub volume_id: u32,
+    /// Taille de cluster imposée, en secteurs ; choisie d'après la taille du volume si `None`.
+    pub override_sectors_per_cluster: Option<u8>,
+    pub reserved_sectors: u16,
+    pub num_fats: u8,
+}
+
+impl Default for FormatOptions<'_> {
+    fn default() -> Self {
+        FormatOptions {
+            volume_label: None,
+            volume_id: 0,
+            override_sectors_per_cluster: None,
+            reserved_sectors: 32,
+            num_fats: 2,
+        }
+    }
+}
+
+/// Partie du volume fraîchement formaté qui n'a pas pu être relue correctement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatCheck {
+    BootSignature,
+    Geometry,
+    BackupBootSector,
+    FatMarkers,
+    FsInfo,
+}
+
+struct Geometry {
+    total_sectors: u32,
+    reserved_sectors: u32,
+    num_fats: u32,
+    sectors_per_cluster: u32,
+    fat_size: u32,
+    cluster_count: u32,
+}
+
+impl Geometry {
+    fn compute(total_sectors: u32, options: &FormatOptions) -> Result<Self, Error> {
+        let sectors_per_cluster = match options.override_sectors_per_cluster {
+            Some(sectors_per_cluster) => sectors_per_cluster as u32,
+            None => CLUSTER_SIZE_TABLE
+                .iter()
+                .find(|&&(limit, _)| total_sectors <= limit)
+                .map_or(0, |&(_, sectors_per_cluster)| sectors_per_cluster),
+        };
+        let reserved_sectors = options.reserved_sectors as u32;
+        let num_fats = options.num_fats as u32;
+
+        if !sectors_per_cluster.is_power_of_two()
+            || num_fats == 0
+            || reserved_sectors <= BACKUP_BOOT_SECTOR as u32 + 1
+            || total_sectors <= reserved_sectors
+        {
+            return Err(Error::InvalidFormatOptions);
+        }
+
+        // Calcul de la taille d'une FAT tel que décrit dans la spécification Microsoft
+        let tmp1 = total_sectors - reserved_sectors;
+        let tmp2 = (256 * sectors_per_cluster + num_fats) / 2;
+        let fat_size = tmp1.div_ceil(tmp2);
+
+        let fat_sectors = num_fats * fat_size;
+        if reserved_sectors + fat_sectors >= total_sectors {
+            return Err(Error::InvalidFormatOptions);
+        }
+        let cluster_count = (total_sectors - reserved_sectors - fat_sectors) / sectors_per_cluster;
+        if !(MIN_CLUSTERS..=MAX_CLUSTERS).contains(&cluster_count) {
+            return Err(Error::InvalidFormatOptions);
+        }
+
+        Ok(Geometry {
+            total_sectors,
+            reserved_sectors,
+            num_fats,
+            sectors_per_cluster,
+            fat_size,
+            cluster_count,
+        })
+    }
+
+    fn first_data_sector(&self) -> u32 {
+        self.reserved_sectors + self.num_fats * self.fat_size
+    }
+}
+
+impl<D: Disk> Fat32<D> {
+    /// Écrit un système de fichiers FAT32 vide sur tout le disque.
+    pub fn format(disk: &mut D, options: &FormatOptions) -> Result<(), Error> {
+        let geometry = Geometry::compute(disk.sector_count(), options)?;
+        let volume_label = match options.volume_label {
+            Some(label) => Some(name::encode_volume_label(label)?),
+            None => None,
+        };
+        let mut buffer = [0u8; SECTOR_SIZE];
+        let mut write_sector = |lba: u32, buffer: &[u8; SECTOR_SIZE]| {
+            disk.write_sector(lba, buffer).map_err(io_at(lba))
+        };
+
+        for lba in 0..geometry.reserved_sectors {
+            write_sector(lba, &buffer)?;
+        }
+
+        {
+            let boot_sector = unsafe { cast_slice_to_struct_mut::<BootSector>(&mut buffer) };
+            boot_sector.jmp_boot = [0xEB, 0x58, 0x90];
+            boot_sector.oem_name = *b"MSWIN4.1";
+            let bpb = &mut boot_sector.bpb;
+            bpb.bytes_per_sector = SECTOR_SIZE as u16;
+            bpb.sectors_per_cluster = geometry.sectors_per_cluster as u8;
+            bpb.reserved_sector_count = options.reserved_sectors;
+            bpb.num_fats = options.num_fats;
+            bpb.media_descriptor = MEDIA_DESCRIPTOR;
+            bpb.sectors_per_track = 63;
+            bpb.num_heads = 255;
+            bpb.total_sectors_32 = geometry.total_sectors;
+            bpb.fat_size_32 = geometry.fat_size;
+            bpb.root_cluster = ROOT_CLUSTER;
+            bpb.fs_info_sector = FS_INFO_SECTOR;
+            bpb.backup_boot_sector = BACKUP_BOOT_SECTOR;
+            bpb.drive_num = 0x80;
+            bpb.boot_signature = 0x29;
+            bpb.volume_id = options.volume_id;
+            bpb.volume_label = volume_label.unwrap_or(*name::NO_VOLUME_LABEL);
+            bpb.fs_type = *b"FAT32   ";
+            boot_sector.boot_signature = 0xAA55;
+        }
+        write_sector(0, &buffer)?;
+        write_sector(BACKUP_BOOT_SECTOR as u32, &buffer)?;
+
+        buffer = [0u8; SECTOR_SIZE];
+        {
+            let fs_info = unsafe { cast_slice_to_struct_mut::<FsInfoSector>(&mut buffer) };
+            fs_info.lead_signature = FS_INFO_LEAD_SIGNATURE;
+            fs_info.struct_signature = FS_INFO_STRUCT_SIGNATURE;
+            // Le cluster racine est déjà utilisé
+            fs_info.free_count = geometry.cluster_count - 1;
+            fs_info.next_free = ROOT_CLUSTER + 1;
+            fs_info.trail_signature = FS_INFO_TRAIL_SIGNATURE;
+        }
+        write_sector(FS_INFO_SECTOR as u32, &buffer)?;
+        write_sector(BACKUP_BOOT_SECTOR as u32 + 1, &buffer)?;
+
+        for fat in 0..geometry.num_fats {
+            let first_sector = geometry.reserved_sectors + fat * geometry.fat_size;
+            buffer = [0u8; SECTOR_SIZE];
+            buffer[0..4].copy_from_slice(&(0x0FFFFF00 | MEDIA_DESCRIPTOR as u32).to_le_bytes());
+            buffer[4..8].copy_from_slice(&0x0FFFFFFFu32.to_le_bytes());
+            buffer[8..12].copy_from_slice(&0x0FFFFFFFu32.to_le_bytes());
+            write_sector(first_sector, &buffer)?;
+
+            buffer = [0u8; SECTOR_SIZE];
+            for i in 1..geometry.fat_size {
+                write_sector(first_sector + i, &buffer)?;
+            }
+        }
+
+        let root_lba = geometry.first_data_sector();
+        for i in 1..geometry.sectors_per_cluster {
+            write_sector(root_lba + i, &buffer)?;
+        }
+        // Windows et dosfstools lisent le nom du volume dans le répertoire racine
+        if let Some(label) = volume_label {
+            buffer[..32].copy_from_slice(&volume_label_entry(label));
+        }
+        write_sector(root_lba, &buffer)?;
+
+        Ok(())
+    }
+
+    /// Formate le disque, vérifie que la structure écrite se relit correctement puis le monte.
+    pub fn format_and_mount(mut disk: D, options: FormatOptions) -> Result<Self, Error> {
+        Self::format(&mut disk, &options)?;
+        Self::verify_format(&disk, &options)?;
+        Self::mount(disk)
+    }
+
+    fn verify_format(disk: &D, options: &FormatOptions) -> Result<(), Error> {
+        let geometry = Geometry::compute(disk.sector_count(), options)?;
+        let read_sector = |lba: u32, buffer: &mut [u8; SECTOR_SIZE]| {
+            disk.read_sector(lba, buffer).map_err(io_at(lba))
+        };
+        let mut boot = [0u8; SECTOR_SIZE];
+        read_sector(0, &mut boot)?;
+
+        let boot_sector = unsafe { cast_slice_to_struct::<BootSector>(&boot) };
+        if boot_sector.boot_signature != 0xAA55 {
+            return Err(Error::FormatMismatch(FormatCheck::BootSignature));
+        }
+
+        let bpb = &boot_sector.bpb;
+        let fs_info_sector = bpb.fs_info_sector as u32;
+        let backup_boot_sector = bpb.backup_boot_sector as u32;
+        if bpb.bytes_per_sector as usize != SECTOR_SIZE
+            || bpb.sectors_per_cluster as u32 != geometry.sectors_per_cluster
+            || bpb.reserved_sector_count as u32 != geometry.reserved_sectors
+            || bpb.num_fats as u32 != geometry.num_fats
+            || bpb.total_sectors_32 != geometry.total_sectors
+            || bpb.fat_size_32 != geometry.fat_size
+            || bpb.root_cluster != ROOT_CLUSTER
+            || fs_info_sector >= geometry.reserved_sectors
+            || backup_boot_sector >= geometry.reserved_sectors
+            || geometry.fat_size * (SECTOR_SIZE as u32 / 4) < geometry.cluster_count + 2
+        {
+            return Err(Error::FormatMismatch(FormatCheck::Geometry));
+        }
+
+        let mut buffer = [0u8; SECTOR_SIZE];
+        read_sector(backup_boot_sector, &mut buffer)?;
+        if buffer != boot {
+            return Err(Error::FormatMismatch(FormatCheck::BackupBootSector));
+        }
+
+        for fat in 0..geometry.num_fats {
+            read_sector(
+                geometry.reserved_sectors + fat * geometry.fat_size,
+                &mut buffer,
+            )?;
+            let entry = |i: usize| {
+                u32::from_le_bytes([
+                    buffer[i * 4],
+                    buffer[i * 4 + 1],
+                    buffer[i * 4 + 2],
+                    buffer[i * 4 + 3],
+                ]) & FAT32_ENTRY_MASK
+            };
+            if entry(0) != 0x0FFFFF00 | MEDIA_DESCRIPTOR as u32
+                || !is_end_of_chain(entry(1))
+                || !is_end_of_chain(entry(ROOT_CLUSTER as usize))
+            {
+                return Err(Error::FormatMismatch(FormatCheck::FatMarkers));
+            }
+        }
+
+        read_sector(fs_info_sector, &mut buffer)?;
+        let fs_info = unsafe { cast_slice_to_struct::<FsInfoSector>(&buffer) };
+        if fs_info.lead_signature != FS_INFO_LEAD_SIGNATURE
+            || fs_info.struct_signature != FS_INFO_STRUCT_SIGNATURE
+            || fs_info.trail_signature != FS_INFO_TRAIL_SIGNATURE
+            || fs_info.free_count >= geometry.cluster_count
+            || fs_info.next_free < 2
+            || fs_info.next_free >= geometry.cluster_count + 2
+        {
+            return Err(Error::FormatMismatch(FormatCheck::FsInfo));
+        }
+
+        Ok(())
+    }
+}
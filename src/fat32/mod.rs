@@ -1,16 +1,82 @@
-use core::fmt;
 use alloc::string::String;
 use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt;
 use core::mem::size_of;
 
+mod builder;
+mod check;
+mod cp437;
+mod dir;
+mod file;
+mod format;
+mod name;
+mod recover;
+mod slice;
+mod time;
+mod walk;
+mod write;
+
+pub use builder::Fat32Builder;
+pub use check::FsCheckReport;
+pub use dir::Dir;
+pub use file::FileMut;
+pub use format::{FormatCheck, FormatOptions};
+pub use recover::DeletedEntry;
+pub use slice::SliceDisk;
+pub use time::{Clock, Timestamp};
+pub use walk::WalkIter;
+pub use write::DeleteOptions;
+
 pub const SECTOR_SIZE: usize = 512;
 
+/// Bits significatifs d'une entrée de FAT32 ; les 4 bits de poids fort sont réservés et
+/// doivent être conservés à l'écriture.
+const FAT32_ENTRY_MASK: u32 = 0x0FFFFFFF;
+const FAT1_CLEAN_SHUTDOWN: u32 = 0x08000000;
+const FAT1_NO_IO_ERROR: u32 = 0x04000000;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Error {
     IoError,
+    /// Erreur d'entrée-sortie sur le secteur `lba`.
+    IoAt {
+        lba: u32,
+    },
     InvalidFat32Structure,
+    /// Un composant du chemin n'existe pas.
     FileNotFound,
+    /// Un composant intermédiaire du chemin, ou la cible d'une opération sur les
+    /// répertoires, est un fichier.
+    NotADirectory,
+    /// La cible d'une opération sur les fichiers est un répertoire.
+    IsADirectory,
+    /// Chemin mal formé : vide, nom qui ne tient pas en 8.3, caractère hors CP437, ou
+    /// répertoire déplacé dans sa propre arborescence.
     InvalidPath,
+    InvalidFormatOptions,
+    FormatMismatch(FormatCheck),
+    DiskFull,
+    AlreadyExists,
+    IncompleteWrite,
+    PathTooDeep,
+    InvalidVolumeLabel,
+    ReadOnly,
+    FileTooLarge,
+    SameFile,
+    InvalidMountOptions,
+    PartitionMismatch,
+    /// Le disque est plus petit que le volume décrit par le secteur de démarrage.
+    TruncatedVolume,
+    /// Position au-delà de la fin d'un fichier.
+    OutOfRange,
+    /// Attributs qui ne peuvent pas être donnés à un fichier (`DIRECTORY`, `VOLUME_ID`,
+    /// bits réservés).
+    InvalidAttributes,
+    /// Le tampon fourni ne peut pas contenir tout le fichier.
+    BufferTooSmall,
+    /// Les clusters d'un fichier supprimé ont été réattribués : son contenu est perdu.
+    ClustersReused,
 }
 
 impl fmt::Display for Error {
@@ -19,15 +85,122 @@ impl fmt::Display for Error {
     }
 }
 
+/// Périphérique de secteurs de 512 octets. Le trait reste utilisable en objet : un volume
+/// peut être monté sur `&mut dyn Disk` ou `&dyn Disk` pour n'instancier le pilote qu'une
+/// fois.
 pub trait Disk {
     fn read_sector(&self, sector_lba: u32, buffer: &mut [u8]) -> Result<(), Error>;
     fn write_sector(&mut self, sector_lba: u32, buffer: &[u8]) -> Result<(), Error>;
     fn sector_count(&self) -> u32;
+
+    /// Indique que `write_sector` échoue toujours ; `sync_fs_info` n'écrit alors rien.
+    fn is_read_only(&self) -> bool {
+        false
+    }
+}
+
+/// Permet de monter un disque emprunté et de le récupérer une fois le volume abandonné.
+impl<D: Disk + ?Sized> Disk for &mut D {
+    fn read_sector(&self, sector_lba: u32, buffer: &mut [u8]) -> Result<(), Error> {
+        (**self).read_sector(sector_lba, buffer)
+    }
+
+    fn write_sector(&mut self, sector_lba: u32, buffer: &[u8]) -> Result<(), Error> {
+        (**self).write_sector(sector_lba, buffer)
+    }
+
+    fn sector_count(&self) -> u32 {
+        (**self).sector_count()
+    }
+
+    fn is_read_only(&self) -> bool {
+        (**self).is_read_only()
+    }
+}
+
+/// Un emprunt partagé ne donne accès au disque qu'en lecture.
+impl<D: Disk + ?Sized> Disk for &D {
+    fn read_sector(&self, sector_lba: u32, buffer: &mut [u8]) -> Result<(), Error> {
+        (**self).read_sector(sector_lba, buffer)
+    }
+
+    fn write_sector(&mut self, _sector_lba: u32, _buffer: &[u8]) -> Result<(), Error> {
+        Err(Error::ReadOnly)
+    }
+
+    fn sector_count(&self) -> u32 {
+        (**self).sector_count()
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+}
+
+/// Options de montage d'un volume.
+#[derive(Debug, Clone, Copy)]
+pub struct MountOptions {
+    /// Nombre maximal de composants parcourus lors de la résolution d'un chemin.
+    pub max_path_depth: u16,
+    /// Taille maximale qu'accepte `read_file`, qui alloue le contenu entier du fichier.
+    pub max_read_size: u32,
+    /// Horloge des dates de création et de modification ; les entrées ne sont pas
+    /// horodatées sans horloge.
+    pub clock: Option<&'static dyn Clock>,
+    /// Marqueur écrit en fin de chaîne, entre `0x0FFFFFF8` et `0x0FFFFFFF` ; tous sont
+    /// acceptés en lecture.
+    pub end_of_chain: u32,
+    /// Premier secteur de la partition d'après l'entrée MBR ; le montage échoue si le
+    /// champ `hidden_sectors` du BPB indique un autre décalage.
+    pub partition_start: Option<u32>,
+    /// Refuse toute écriture avec `Error::ReadOnly`.
+    pub read_only: bool,
+    /// Échoue au montage au lieu d'ignorer un secteur FSInfo absent ou invalide.
+    pub strict: bool,
+    /// Garde en mémoire le dernier secteur de FAT lu, ce qui accélère le parcours des
+    /// chaînes.
+    pub fat_cache: bool,
+    /// N'écrit que la première FAT pendant la session ; `flush` et `sync_fats` recopient
+    /// ensuite les secteurs modifiés dans les autres copies.
+    pub deferred_fat_mirror: bool,
+}
+
+impl Default for MountOptions {
+    fn default() -> Self {
+        MountOptions {
+            max_path_depth: 64,
+            max_read_size: 16 * 1024 * 1024,
+            clock: None,
+            end_of_chain: 0x0FFFFFFF,
+            partition_start: None,
+            read_only: false,
+            strict: false,
+            fat_cache: false,
+            deferred_fat_mirror: false,
+        }
+    }
 }
 
 pub struct Fat32<D: Disk> {
     disk: D,
     info: FsInfo,
+    options: MountOptions,
+    fat1_entry: u32,
+    /// Nombre de clusters libres (`None` s'il est inconnu) et indice de recherche du
+    /// prochain cluster libre, recopiés dans le secteur FSInfo par `sync_fs_info`.
+    free_count: Option<u32>,
+    next_free: u32,
+    /// `free_count` ou `next_free` ont changé depuis la dernière écriture du secteur FSInfo.
+    fs_info_dirty: bool,
+    /// Tampon de secteur partagé par les routines FAT et répertoire, au lieu d'un tableau
+    /// de 512 octets sur la pile à chaque niveau d'appel ; dans une `RefCell` pour servir
+    /// aussi aux lectures, qui ne prennent que `&self`.
+    scratch: RefCell<[u8; SECTOR_SIZE]>,
+    /// Dernier secteur de FAT lu, si `MountOptions::fat_cache` est actif.
+    fat_cache: RefCell<Option<(u32, [u8; SECTOR_SIZE])>>,
+    /// Premier et dernier secteurs de la première FAT pas encore recopiés dans les autres,
+    /// si `MountOptions::deferred_fat_mirror` est actif.
+    unmirrored_fat_sectors: Option<(u32, u32)>,
 }
 
 pub struct FileInfo {
@@ -35,28 +208,113 @@ pub struct FileInfo {
     pub is_directory: bool,
     pub size: u32,
     pub start_cluster: u32,
+    pub attributes: u8,
+    /// Dates de création et de dernière modification, `None` si l'entrée n'est pas
+    /// horodatée.
+    pub created: Option<Timestamp>,
+    pub modified: Option<Timestamp>,
+}
+
+impl FileInfo {
+    /// L'entrée `..` d'un sous-répertoire de premier niveau a 0 pour premier cluster et
+    /// désigne la racine, `root_cluster`.
+    fn from_entry(entry: &DirEntry, root_cluster: u32) -> Self {
+        let is_directory = (entry.attributes & ATTR_DIRECTORY) != 0;
+        let mut start_cluster =
+            (entry.first_cluster_high as u32) << 16 | (entry.first_cluster_low as u32);
+        if is_directory && start_cluster == 0 && entry.name == *b"..         " {
+            start_cluster = root_cluster;
+        }
+
+        FileInfo {
+            name: name::decode_short_name(&entry.name, entry.nt_reserved),
+            is_directory,
+            size: entry.file_size,
+            start_cluster,
+            attributes: entry.attributes,
+            created: Timestamp::from_fat(
+                entry.create_date,
+                entry.create_time,
+                entry.create_time_tenth,
+            ),
+            modified: Timestamp::from_fat(entry.write_date, entry.write_time, 0),
+        }
+    }
+}
+
+/// Précise le secteur en cause d'une erreur d'entrée-sortie renvoyée par le disque.
+fn io_at(lba: u32) -> impl FnOnce(Error) -> Error {
+    move |e| match e {
+        Error::IoError => Error::IoAt { lba },
+        e => e,
+    }
+}
+
+/// Indique si une entrée de FAT marque la fin d'une chaîne.
+fn is_end_of_chain(entry: u32) -> bool {
+    (0x0FFFFFF8..=0x0FFFFFFF).contains(&entry)
+}
+
+/// Indique si `path` désigne la racine, qui n'a pas d'entrée de répertoire.
+fn is_root_path(path: &str) -> bool {
+    path.split('/').all(|part| part.is_empty())
+}
+
+/// Sépare un chemin en répertoire parent et nom de l'élément.
+fn split_path(path: &str) -> Result<(&str, &str), Error> {
+    let path = path.trim_end_matches('/');
+    let (parent, leaf) = match path.rsplit_once('/') {
+        Some((parent, leaf)) => (parent, leaf),
+        None => ("", path),
+    };
+    if leaf.is_empty() {
+        return Err(Error::InvalidPath);
+    }
+    Ok((parent, leaf))
+}
+
+/// Position d'une entrée de répertoire sur le disque.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EntryLocation {
+    lba: u32,
+    offset: usize,
 }
 
 impl<D: Disk> Fat32<D> {
     pub fn mount(disk: D) -> Result<Self, Error> {
-	if disk.sector_count() == 0 {
+        Self::mount_with_options(disk, MountOptions::default())
+    }
+
+    pub fn mount_with_options(disk: D, options: MountOptions) -> Result<Self, Error> {
+        if disk.sector_count() == 0 {
             return Err(Error::IoError);
         }
+        if !is_end_of_chain(options.end_of_chain) {
+            return Err(Error::InvalidMountOptions);
+        }
 
         let mut buffer = [0u8; SECTOR_SIZE];
-        disk.read_sector(0, &mut buffer)?;
-        
+        disk.read_sector(0, &mut buffer).map_err(io_at(0))?;
+
         let boot_sector = unsafe { cast_slice_to_struct::<BootSector>(&buffer) };
-        
+
         if boot_sector.boot_signature != 0xAA55 {
             return Err(Error::InvalidFat32Structure);
         }
 
         let bpb = &boot_sector.bpb;
-        
+
         let bytes_per_sector = bpb.bytes_per_sector as u32;
         if bytes_per_sector != 512 {
-             return Err(Error::InvalidFat32Structure);
+            return Err(Error::InvalidFat32Structure);
+        }
+
+        let hidden_sectors = bpb.hidden_sectors;
+        if options
+            .partition_start
+            .is_some_and(|start| start != hidden_sectors)
+        {
+            return Err(Error::PartitionMismatch);
         }
 
         let reserved_sector_count = bpb.reserved_sector_count as u32;
@@ -65,13 +323,36 @@ impl<D: Disk> Fat32<D> {
         let root_cluster = bpb.root_cluster;
 
         let first_fat_sector = reserved_sector_count;
-        let fat_sectors = num_fats * fat_size;
-        let first_data_sector = reserved_sector_count + fat_sectors;
+        let fat_sectors = num_fats.checked_mul(fat_size);
+        let first_data_sector = fat_sectors
+            .and_then(|fat_sectors| fat_sectors.checked_add(reserved_sector_count))
+            .ok_or(Error::InvalidFat32Structure)?;
 
         let total_sectors = bpb.total_sectors_32;
+        if total_sectors > disk.sector_count() {
+            return Err(Error::TruncatedVolume);
+        }
+        if bpb.sectors_per_cluster == 0 || first_data_sector >= total_sectors {
+            return Err(Error::InvalidFat32Structure);
+        }
         let data_sectors = total_sectors - first_data_sector;
         let cluster_count = data_sectors / (bpb.sectors_per_cluster as u32);
 
+        // 0 et 0xFFFF signifient qu'il n'y a pas de copie de secours ; ailleurs, elle doit
+        // se trouver dans la zone réservée
+        let backup_boot_sector = match bpb.backup_boot_sector as u32 {
+            0 | 0xFFFF => None,
+            sector if sector < reserved_sector_count => Some(sector),
+            _ => return Err(Error::InvalidFat32Structure),
+        };
+
+        // Certaines cartes indiquent un secteur FSInfo nul ou hors de la zone réservée : le
+        // volume reste utilisable, le nombre de clusters libres est simplement inconnu.
+        let fs_info_sector = bpb.fs_info_sector as u32;
+        let fs_info_sector = (fs_info_sector != 0
+            && fs_info_sector < reserved_sector_count
+            && Some(fs_info_sector) != backup_boot_sector)
+            .then_some(fs_info_sector);
 
         let fs_info = FsInfo {
             bytes_per_sector,
@@ -83,143 +364,587 @@ impl<D: Disk> Fat32<D> {
             first_fat_sector,
             first_data_sector,
             cluster_count,
+            fs_info_sector,
+            backup_boot_sector,
+            hidden_sectors,
         };
 
+        // FAT[1] contient les indicateurs d'arrêt propre et d'erreur matérielle
+        disk.read_sector(first_fat_sector, &mut buffer)
+            .map_err(io_at(first_fat_sector))?;
+        let fat1_entry = u32::from_le_bytes([buffer[4], buffer[5], buffer[6], buffer[7]]);
 
-        Ok(Fat32 { 
-	    disk,
-	    info: fs_info, 
-	})
+        let (mut free_count, mut next_free) = (None, 2);
+        if options.strict && fs_info.fs_info_sector.is_none() {
+            return Err(Error::InvalidFat32Structure);
+        }
+        if let Some(fs_info_lba) = fs_info.fs_info_sector {
+            disk.read_sector(fs_info_lba, &mut buffer)
+                .map_err(io_at(fs_info_lba))?;
+            let fs_info_sector = unsafe { cast_slice_to_struct::<FsInfoSector>(&buffer) };
+            if fs_info_sector.lead_signature == FS_INFO_LEAD_SIGNATURE
+                && fs_info_sector.struct_signature == FS_INFO_STRUCT_SIGNATURE
+                && fs_info_sector.trail_signature == FS_INFO_TRAIL_SIGNATURE
+            {
+                let stored_free_count = fs_info_sector.free_count;
+                let stored_next_free = fs_info_sector.next_free;
+                free_count = (stored_free_count <= cluster_count).then_some(stored_free_count);
+                if (2..cluster_count + 2).contains(&stored_next_free) {
+                    next_free = stored_next_free;
+                }
+            } else if options.strict {
+                return Err(Error::InvalidFat32Structure);
+            }
+        }
+
+        Ok(Fat32 {
+            disk,
+            info: fs_info,
+            options,
+            fat1_entry,
+            free_count,
+            next_free,
+            fs_info_dirty: false,
+            scratch: RefCell::new([0u8; SECTOR_SIZE]),
+            fat_cache: RefCell::new(None),
+            unmirrored_fat_sectors: None,
+        })
+    }
+
+    pub fn layout(&self) -> Layout {
+        Layout {
+            first_fat_sector: self.info.first_fat_sector,
+            fat_sectors: self.info.num_fats * self.info.fat_size,
+            first_data_sector: self.info.first_data_sector,
+            cluster_count: self.info.cluster_count,
+            sectors_per_cluster: self.info.sectors_per_cluster,
+            fat_size: self.info.fat_size,
+            root_cluster: self.info.root_cluster,
+        }
+    }
+
+    /// Décalage de la partition depuis le début du disque, tel qu'enregistré dans le BPB.
+    pub fn hidden_sectors(&self) -> u32 {
+        self.info.hidden_sectors
+    }
+
+    /// Indique si le volume a été démonté proprement lors de la session précédente.
+    pub fn was_cleanly_unmounted(&self) -> bool {
+        self.fat1_entry & FAT1_CLEAN_SHUTDOWN != 0
+    }
+
+    /// Indique si une erreur d'entrée/sortie matérielle a été signalée dans FAT[1]
+    /// lors d'une session précédente.
+    pub fn had_io_error(&self) -> bool {
+        self.fat1_entry & FAT1_NO_IO_ERROR == 0
     }
 
-    fn cluster_to_lba(&self, cluster: u32) -> u32 {
-            let cluster_offset = cluster - 2;
-            self.info.first_data_sector + (cluster_offset * self.info.sectors_per_cluster)
-    }	
+    /// Renvoie le premier secteur d'un cluster de la zone de données.
+    pub fn cluster_to_lba(&self, cluster: u32) -> Result<u32, Error> {
+        if cluster < 2 || cluster >= self.info.cluster_count + 2 {
+            return Err(Error::InvalidFat32Structure);
+        }
+        let cluster_offset = cluster - 2;
+        Ok(self.info.first_data_sector + (cluster_offset * self.info.sectors_per_cluster))
+    }
+
+    /// Renvoie le cluster de données qui contient le secteur `lba`, ou `None` si le secteur
+    /// appartient à la zone réservée, aux FAT ou se trouve après le dernier cluster.
+    pub fn lba_to_cluster(&self, lba: u32) -> Option<u32> {
+        let data_offset = lba.checked_sub(self.info.first_data_sector)?;
+        let cluster_offset = data_offset / self.info.sectors_per_cluster;
+        (cluster_offset < self.info.cluster_count).then_some(cluster_offset + 2)
+    }
+
+    /// Cluster d'indice `index` de la chaîne qui commence à `first_cluster`.
+    fn cluster_at(&self, first_cluster: u32, index: u32) -> Result<u32, Error> {
+        if first_cluster < 2 {
+            return Err(Error::OutOfRange);
+        }
+        let mut cluster = first_cluster;
+        for _ in 0..index {
+            cluster = self.get_fat_entry(cluster)?;
+            if is_end_of_chain(cluster) {
+                return Err(Error::OutOfRange);
+            }
+        }
+        Ok(cluster)
+    }
+
+    /// Cluster d'indice `index` du fichier `path`.
+    pub fn nth_cluster(&self, path: &str, index: u32) -> Result<u32, Error> {
+        let file = self.find_entry(path, self.info.root_cluster)?;
+        self.cluster_at(file.start_cluster, index)
+    }
+
+    /// Lit le contenu d'un cluster dans `buffer`, dans la limite de sa taille, et renvoie
+    /// le nombre d'octets lus.
+    pub fn read_cluster(&self, cluster: u32, buffer: &mut [u8]) -> Result<usize, Error> {
+        let lba = self.cluster_to_lba(cluster)?;
+        let len = buffer.len().min(self.cluster_size() as usize);
+
+        for (i, chunk) in buffer[..len].chunks_mut(SECTOR_SIZE).enumerate() {
+            let sector_lba = lba + i as u32;
+            if chunk.len() == SECTOR_SIZE {
+                self.disk
+                    .read_sector(sector_lba, chunk)
+                    .map_err(io_at(sector_lba))?;
+            } else {
+                self.with_sector(sector_lba, |sector| {
+                    chunk.copy_from_slice(&sector[..chunk.len()])
+                })?;
+            }
+        }
+        Ok(len)
+    }
 
     fn get_fat_entry(&self, cluster: u32) -> Result<u32, Error> {
         if cluster < 2 || cluster >= self.info.cluster_count + 2 {
-                return Err(Error::InvalidFat32Structure);
-    	}
-        
-    	let fat_entry_offset = cluster * 4; 
-    	let fat_sector_num = self.info.first_fat_sector + (fat_entry_offset / self.info.bytes_per_sector);
-    	let fat_entry_in_sector = fat_entry_offset % self.info.bytes_per_sector;
-    	let mut buffer = [0u8; SECTOR_SIZE];
-    	self.disk.read_sector(fat_sector_num, &mut buffer)?; 
-
-    	let entry_bytes: [u8; 4] = [
-            buffer[fat_entry_in_sector as usize],
-            buffer[(fat_entry_in_sector + 1) as usize],
-            buffer[(fat_entry_in_sector + 2) as usize],
-            buffer[(fat_entry_in_sector + 3) as usize],
-    	];
-
-        let entry = u32::from_le_bytes(entry_bytes);
-        
-        Ok(entry & 0x0FFFFFFF)
+            return Err(Error::InvalidFat32Structure);
+        }
+
+        let fat_entry_offset = cluster * 4;
+        let fat_sector_num =
+            self.info.first_fat_sector + (fat_entry_offset / self.info.bytes_per_sector);
+        let fat_entry_in_sector = fat_entry_offset % self.info.bytes_per_sector;
+
+        let entry = self.with_fat_sector(fat_sector_num, |buffer| {
+            u32::from_le_bytes([
+                buffer[fat_entry_in_sector as usize],
+                buffer[(fat_entry_in_sector + 1) as usize],
+                buffer[(fat_entry_in_sector + 2) as usize],
+                buffer[(fat_entry_in_sector + 3) as usize],
+            ])
+        })?;
+
+        Ok(entry & FAT32_ENTRY_MASK)
     }
 
-    pub fn read_directory(&self, start_cluster: u32) -> Result<Vec<FileInfo>, Error> {
+    /// Lit un secteur dans le tampon de travail du volume et le passe à `f`.
+    ///
+    /// `f` ne doit pas elle-même accéder au disque : le tampon est emprunté pendant
+    /// tout son appel.
+    fn with_sector<T>(
+        &self,
+        lba: u32,
+        f: impl FnOnce(&[u8; SECTOR_SIZE]) -> T,
+    ) -> Result<T, Error> {
+        let mut scratch = self.scratch.borrow_mut();
+        self.disk
+            .read_sector(lba, &mut scratch[..])
+            .map_err(io_at(lba))?;
+        Ok(f(&scratch))
+    }
+
+    /// Comme `with_sector`, en passant par le cache de FAT s'il est actif.
+    fn with_fat_sector<T>(
+        &self,
+        lba: u32,
+        f: impl FnOnce(&[u8; SECTOR_SIZE]) -> T,
+    ) -> Result<T, Error> {
+        if !self.options.fat_cache {
+            return self.with_sector(lba, f);
+        }
+
+        let mut cache = self.fat_cache.borrow_mut();
+        if let Some((cached, sector)) = &*cache
+            && *cached == lba
+        {
+            return Ok(f(sector));
+        }
+        let (_, sector) = cache.insert((lba, [0u8; SECTOR_SIZE]));
+        if let Err(e) = self.disk.read_sector(lba, sector) {
+            *cache = None;
+            return Err(io_at(lba)(e));
+        }
+        Ok(f(sector))
+    }
+
+    /// Copie brute du secteur `lba` du disque, pour le débogage (secteur de démarrage,
+    /// FAT, etc.).
+    pub fn dump_sector(&self, lba: u32) -> Result<[u8; SECTOR_SIZE], Error> {
+        self.with_sector(lba, |buffer| *buffer)
+    }
+
+    fn cluster_size(&self) -> u32 {
+        self.info.sectors_per_cluster * self.info.bytes_per_sector
+    }
+
+    /// Nombre de clusters qu'occupent `len` octets (aucun pour un fichier vide).
+    pub fn clusters_needed(&self, len: u64) -> u32 {
+        u32::try_from(len.div_ceil(self.cluster_size() as u64)).unwrap_or(u32::MAX)
+    }
+
+    /// Parcourt tous les emplacements d'un répertoire, en suivant sa chaîne de clusters,
+    /// jusqu'à ce que `visit` renvoie `Some`.
+    fn scan_directory<T>(
+        &self,
+        start_cluster: u32,
+        mut visit: impl FnMut(&DirEntry, EntryLocation) -> Option<T>,
+    ) -> Result<Option<T>, Error> {
         let mut current_cluster = start_cluster;
-        let mut entries = Vec::new();
-        let mut buffer = [0u8; SECTOR_SIZE];
-        
-        loop {
-            if current_cluster >= 0x0FFFFFF8 {
-                break;
-            }
+        let entries_per_sector = SECTOR_SIZE / size_of::<DirEntry>();
 
-            let lba = self.cluster_to_lba(current_cluster);
-            let sectors_per_cluster = self.info.sectors_per_cluster;
-            let entries_per_sector = SECTOR_SIZE / size_of::<DirEntry>();
-            
-            for i in 0..sectors_per_cluster {
-                self.disk.read_sector(lba + i, &mut buffer)?;
-                
-                for j in 0..entries_per_sector {
-                    let offset = j * size_of::<DirEntry>();
-                    let entry = unsafe { cast_slice_to_struct::<DirEntry>(&buffer[offset..]) };
-
-                    if entry.name[0] == 0x00 { 
-                        return Ok(entries);
-                    }
-                    
-                    if entry.name[0] == 0xE5 {
-                        continue;
-                    }
-                    
-                    if entry.attributes == ATTR_LFN {
-                        continue;
-                    }
-                    
-                    let name = String::from_utf8_lossy(&entry.name).trim_end().to_string();
-                    let file_info = FileInfo {
-                        name,
-                        is_directory: (entry.attributes & ATTR_DIRECTORY) != 0,
-                        size: entry.file_size,
-                        start_cluster: (entry.first_cluster_high as u32) << 16 | (entry.first_cluster_low as u32),
-                    };
-                    entries.push(file_info);
+        while !is_end_of_chain(current_cluster) {
+            let lba = self.cluster_to_lba(current_cluster)?;
+
+            for i in 0..self.info.sectors_per_cluster {
+                let found = self.with_sector(lba + i, |buffer| {
+                    (0..entries_per_sector).find_map(|j| {
+                        let offset = j * size_of::<DirEntry>();
+                        let entry = unsafe { cast_slice_to_struct::<DirEntry>(&buffer[offset..]) };
+                        let location = EntryLocation {
+                            lba: lba + i,
+                            offset,
+                        };
+                        visit(entry, location)
+                    })
+                })?;
+                if found.is_some() {
+                    return Ok(found);
                 }
             }
             current_cluster = self.get_fat_entry(current_cluster)?;
         }
 
+        Ok(None)
+    }
+
+    pub fn read_directory(&self, start_cluster: u32) -> Result<Vec<FileInfo>, Error> {
+        let mut entries = Vec::new();
+
+        self.scan_directory(start_cluster, |entry, _| {
+            if entry.name[0] == 0x00 {
+                return Some(());
+            }
+            if entry.name[0] != 0xE5
+                && !entry.is_lfn_or_volume_label()
+                && !entry.has_invalid_attributes()
+            {
+                entries.push(FileInfo::from_entry(entry, self.info.root_cluster));
+            }
+            None
+        })?;
+
         Ok(entries)
     }
 
+    /// Renvoie le nom du volume, lu dans l'entrée `VOLUME_ID` du répertoire racine ou, à
+    /// défaut, dans le secteur de démarrage.
+    pub fn volume_label(&self) -> Result<Option<String>, Error> {
+        let from_root = self.scan_directory(self.info.root_cluster, |entry, _| {
+            if entry.name[0] == 0x00 {
+                return Some(None);
+            }
+            (entry.name[0] != 0xE5 && entry.attributes == ATTR_VOLUME_ID)
+                .then(|| Some(name::decode_padded(&entry.name)))
+        })?;
+        if let Some(label) = from_root.flatten() {
+            return Ok(Some(label));
+        }
+
+        let label = self.with_sector(0, |buffer| {
+            let boot_sector = unsafe { cast_slice_to_struct::<BootSector>(buffer) };
+            boot_sector.bpb.volume_label
+        })?;
+        if &label == name::NO_VOLUME_LABEL {
+            return Ok(None);
+        }
+        Ok(Some(name::decode_padded(&label)))
+    }
+
+    /// Nom OEM du secteur de démarrage (`MSWIN4.1` pour un volume formaté ici).
+    pub fn oem_name(&self) -> Result<String, Error> {
+        self.with_sector(0, |buffer| {
+            let boot_sector = unsafe { cast_slice_to_struct::<BootSector>(buffer) };
+            name::decode_padded(&boot_sector.oem_name)
+        })
+    }
+
+    /// Nombre de clusters libres, s'il est connu.
+    pub fn free_clusters(&self) -> Option<u32> {
+        self.free_count
+    }
+
+    /// Part des clusters utilisés, entre 0,0 et 1,0. Comme `free_clusters`, renvoie `None`
+    /// tant que le nombre de clusters libres est inconnu (voir `recompute_free_count`), et
+    /// aussi si le volume n'a aucun cluster.
+    pub fn usage_ratio(&self) -> Option<f32> {
+        if self.info.cluster_count == 0 {
+            return None;
+        }
+        let free = self.free_count?.min(self.info.cluster_count);
+        Some((self.info.cluster_count - free) as f32 / self.info.cluster_count as f32)
+    }
+
+    /// Indique s'il ne reste aucun cluster libre, `None` si ce nombre est inconnu.
+    pub fn is_full(&self) -> Option<bool> {
+        Some(self.free_count? == 0)
+    }
+
+    /// Recompte les clusters libres en parcourant la première FAT.
+    pub fn recompute_free_count(&mut self) -> Result<u32, Error> {
+        let mut free_count = 0;
+        self.scan_fat(|_, entry| {
+            if entry == 0 {
+                free_count += 1;
+            }
+        })?;
+
+        if self.free_count != Some(free_count) {
+            self.free_count = Some(free_count);
+            self.fs_info_dirty = true;
+        }
+        Ok(free_count)
+    }
+
+    /// Plages `(premier cluster, longueur)` de clusters libres contigus, dans l'ordre.
+    pub fn free_extents(&self) -> Result<Vec<(u32, u32)>, Error> {
+        let mut extents: Vec<(u32, u32)> = Vec::new();
+        self.scan_fat(|cluster, entry| {
+            if entry != 0 {
+                return;
+            }
+            match extents.last_mut() {
+                Some((start, length)) if *start + *length == cluster => *length += 1,
+                _ => extents.push((cluster, 1)),
+            }
+        })?;
+        Ok(extents)
+    }
+
+    /// Appelle `f(cluster, valeur)` pour chaque cluster de données, dans l'ordre, en ne
+    /// lisant qu'une fois chaque secteur de la première FAT.
+    pub fn scan_fat<F: FnMut(u32, u32)>(&self, mut f: F) -> Result<(), Error> {
+        let entries_per_sector = self.info.bytes_per_sector / 4;
+        let end = self.info.cluster_count + 2;
+
+        let mut cluster = 2;
+        while cluster < end {
+            let lba = self.info.first_fat_sector + cluster / entries_per_sector;
+            // Copie du secteur : `f` peut lui-même relire le volume
+            let sector = self.with_sector(lba, |buffer| *buffer)?;
+            let first = cluster % entries_per_sector;
+            let last = entries_per_sector.min(first + end - cluster);
+            for entry in sector[first as usize * 4..last as usize * 4].chunks_exact(4) {
+                f(
+                    cluster,
+                    u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]) & FAT32_ENTRY_MASK,
+                );
+                cluster += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Cherche l'entrée dont le nom 8.3, tel qu'il est stocké, vaut `short_name`.
+    fn find_in_directory(
+        &self,
+        dir_cluster: u32,
+        short_name: &[u8; 11],
+    ) -> Result<Option<(FileInfo, EntryLocation)>, Error> {
+        let found = self.scan_directory(dir_cluster, |entry, location| {
+            if entry.name[0] == 0x00 {
+                return Some(None);
+            }
+            if entry.name[0] == 0xE5
+                || entry.is_lfn_or_volume_label()
+                || entry.has_invalid_attributes()
+            {
+                return None;
+            }
+            if entry.name == *short_name {
+                let info = FileInfo::from_entry(entry, self.info.root_cluster);
+                return Some(Some((info, location)));
+            }
+            None
+        })?;
+
+        Ok(found.flatten())
+    }
+
+    /// Cherche l'entrée `name` d'un répertoire : sous sa forme 8.3, puis, à défaut, avec
+    /// ses minuscules accentuées telles quelles, pour retrouver un nom affiché par
+    /// `read_directory` mais que DOS n'aurait pas écrit.
+    fn find_named(
+        &self,
+        dir_cluster: u32,
+        name: &str,
+    ) -> Result<Option<(FileInfo, EntryLocation)>, Error> {
+        let short_name = name::encode_lookup_name(name)?;
+        if let Some(found) = self.find_in_directory(dir_cluster, &short_name)? {
+            return Ok(Some(found));
+        }
+        match name::encode_stored_short_name(name) {
+            Ok(stored) if stored != short_name => self.find_in_directory(dir_cluster, &stored),
+            _ => Ok(None),
+        }
+    }
+
     pub fn find_entry(&self, path: &str, start_cluster: u32) -> Result<FileInfo, Error> {
         let parts: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        if parts.len() > self.options.max_path_depth as usize {
+            return Err(Error::PathTooDeep);
+        }
         let mut current_cluster = start_cluster;
-        let mut last_info = None;
 
         for (i, part) in parts.iter().enumerate() {
-            let entries = self.read_directory(current_cluster)?;
-            let found = entries.into_iter().find(|e| e.name.eq_ignore_ascii_case(part));
-
-            match found {
-                Some(info) => {
+            match self.find_named(current_cluster, part)? {
+                Some((info, _)) => {
                     if i == parts.len() - 1 {
                         return Ok(info);
                     }
-                    if !info.is_directory {
-                        return Err(Error::InvalidPath);
-                    }
-                    current_cluster = info.start_cluster;
-                    last_info = Some(info);
+                    current_cluster = self.subdirectory_cluster(&info)?;
                 }
                 None => return Err(Error::FileNotFound),
             }
         }
-        last_info.ok_or(Error::InvalidPath)
+        // Chemin vide : le répertoire de départ lui-même
+        Err(Error::IsADirectory)
+    }
+
+    /// Renvoie le premier cluster du répertoire désigné par `path`.
+    fn directory_cluster(&self, path: &str) -> Result<u32, Error> {
+        if is_root_path(path) {
+            return Ok(self.info.root_cluster);
+        }
+
+        let info = self.find_entry(path, self.info.root_cluster)?;
+        self.subdirectory_cluster(&info)
+    }
+
+    /// Premier cluster du répertoire `info`, vérifié avant d'y descendre : une entrée de
+    /// répertoire corrompue ne doit pas mener hors de la zone de données.
+    fn subdirectory_cluster(&self, info: &FileInfo) -> Result<u32, Error> {
+        if !info.is_directory {
+            return Err(Error::NotADirectory);
+        }
+        if info.start_cluster < 2 || info.start_cluster >= self.info.cluster_count + 2 {
+            return Err(Error::InvalidFat32Structure);
+        }
+        Ok(info.start_cluster)
     }
 
+    /// Renvoie le premier cluster du répertoire parent de `path` et le nom de l'élément.
+    /// La racine n'ayant pas de parent, `"/"` donne `InvalidPath`.
+    pub fn parent_of(&self, path: &str) -> Result<(u32, String), Error> {
+        let (parent, leaf) = split_path(path)?;
+        Ok((self.directory_cluster(parent)?, String::from(leaf)))
+    }
+
+    /// Renvoie l'entrée désignée par `path` et sa position sur le disque.
+    fn locate(&self, path: &str) -> Result<(FileInfo, EntryLocation), Error> {
+        if is_root_path(path) {
+            return Err(Error::IsADirectory);
+        }
+        let (dir_cluster, leaf) = self.parent_of(path)?;
+        self.find_named(dir_cluster, &leaf)?
+            .ok_or(Error::FileNotFound)
+    }
+
+    /// Indique si `a` et `b` désignent le même fichier, c'est-à-dire le même premier cluster.
+    pub fn same_file(&self, a: &str, b: &str) -> Result<bool, Error> {
+        Ok(self.file_identity(a)? == self.file_identity(b)?)
+    }
+
+    /// Premier cluster de `path`, ou position de son entrée pour un fichier vide qui n'a pas
+    /// de cluster.
+    fn file_identity(&self, path: &str) -> Result<(u32, Option<EntryLocation>), Error> {
+        if is_root_path(path) {
+            return Ok((self.info.root_cluster, None));
+        }
+        let (info, location) = self.locate(path)?;
+        if info.start_cluster == 0 {
+            return Ok((0, Some(location)));
+        }
+        Ok((info.start_cluster, None))
+    }
+
+    /// Renvoie les 32 octets de l'entrée de répertoire de `path`, tels qu'ils sont stockés.
+    pub fn raw_entry(&self, path: &str) -> Result<[u8; 32], Error> {
+        let (_, location) = self.locate(path)?;
+        self.read_entry(location)
+    }
+
+    fn read_entry(&self, location: EntryLocation) -> Result<[u8; 32], Error> {
+        self.with_sector(location.lba, |buffer| {
+            let mut raw = [0u8; 32];
+            raw.copy_from_slice(&buffer[location.offset..location.offset + 32]);
+            raw
+        })
+    }
+
+    /// Nombre de clusters de la chaîne qui commence à `start_cluster`, fin de chaîne
+    /// comprise. Un cluster libre ou réservé au milieu de la chaîne, ou une chaîne qui
+    /// boucle, donne `InvalidFat32Structure`.
+    pub fn chain_length(&self, start_cluster: u32) -> Result<u32, Error> {
+        let mut cluster = start_cluster;
+        let mut length = 0;
+
+        while length < self.info.cluster_count {
+            let next = self.get_fat_entry(cluster)?;
+            length += 1;
+            if is_end_of_chain(next) {
+                return Ok(length);
+            }
+            if next < 2 || next >= self.info.cluster_count + 2 {
+                return Err(Error::InvalidFat32Structure);
+            }
+            cluster = next;
+        }
+
+        // Plus de maillons que de clusters : la chaîne boucle
+        Err(Error::InvalidFat32Structure)
+    }
+
+    /// Vérifie que la taille d'un fichier correspond à la longueur de sa chaîne de clusters.
+    pub fn verify_file(&self, path: &str) -> Result<(), Error> {
+        let file = self.find_entry(path, self.info.root_cluster)?;
+        if file.is_directory {
+            return Err(Error::IsADirectory);
+        }
+
+        let chain_clusters = if file.start_cluster == 0 {
+            0
+        } else {
+            self.chain_length(file.start_cluster)?
+        };
+        if chain_clusters != self.clusters_needed(file.size as u64) {
+            return Err(Error::InvalidFat32Structure);
+        }
+        Ok(())
+    }
 
     pub fn read_file(&self, file: &FileInfo) -> Result<Vec<u8>, Error> {
         if file.is_directory {
-            return Err(Error::IoError);
+            return Err(Error::IsADirectory);
+        }
+        // Une taille corrompue ne doit pas provoquer une allocation démesurée
+        let volume_size = self.info.cluster_count as u64 * self.cluster_size() as u64;
+        if file.size > self.options.max_read_size || file.size as u64 > volume_size {
+            return Err(Error::FileTooLarge);
         }
 
         let mut content = Vec::with_capacity(file.size as usize);
         let mut current_cluster = file.start_cluster;
         let mut bytes_remaining = file.size;
-        let mut buffer = [0u8; SECTOR_SIZE];
 
         while bytes_remaining > 0 {
-            if current_cluster >= 0x0FFFFFF8 || current_cluster < 2 {
+            if !(2..0x0FFFFFF8).contains(&current_cluster) {
                 break;
             }
 
-            let lba = self.cluster_to_lba(current_cluster);
-            
+            let lba = self.cluster_to_lba(current_cluster)?;
+
             for i in 0..self.info.sectors_per_cluster {
-                if bytes_remaining == 0 { break; }
-                
-                self.disk.read_sector(lba + i, &mut buffer)?;
-                
+                if bytes_remaining == 0 {
+                    break;
+                }
+
                 let to_copy = core::cmp::min(bytes_remaining, SECTOR_SIZE as u32);
-                content.extend_from_slice(&buffer[..to_copy as usize]);
+                self.with_sector(lba + i, |buffer| {
+                    content.extend_from_slice(&buffer[..to_copy as usize])
+                })?;
                 bytes_remaining -= to_copy;
             }
 
@@ -228,50 +953,91 @@ impl<D: Disk> Fat32<D> {
 
         Ok(content)
     }
-}
 
+    /// Lit le fichier `path` dans `buffer`, sans allocation, et renvoie le nombre d'octets
+    /// lus, au plus la taille du fichier. Avec `whole_file`, un fichier plus grand que
+    /// `buffer` donne `Error::BufferTooSmall` au lieu d'être lu en partie.
+    pub fn read_file_into(
+        &self,
+        path: &str,
+        buffer: &mut [u8],
+        whole_file: bool,
+    ) -> Result<usize, Error> {
+        let file = self.find_entry(path, self.info.root_cluster)?;
+        if file.is_directory {
+            return Err(Error::IsADirectory);
+        }
+        let len = buffer.len().min(file.size as usize);
+        if whole_file && len < file.size as usize {
+            return Err(Error::BufferTooSmall);
+        }
+
+        let mut cluster = file.start_cluster;
+        let mut chunks = buffer[..len]
+            .chunks_mut(self.cluster_size() as usize)
+            .peekable();
+        while let Some(chunk) = chunks.next() {
+            self.read_cluster(cluster, chunk)?;
+            if chunks.peek().is_some() {
+                cluster = self.get_fat_entry(cluster)?;
+            }
+        }
+        Ok(len)
+    }
+}
 
-#[repr(packed)]
+#[repr(C, packed)]
 #[allow(dead_code)]
 pub struct BiosParameterBlock {
-    pub bytes_per_sector: u16,        
-    pub sectors_per_cluster: u8,       
-    pub reserved_sector_count: u16,  
-    pub num_fats: u8,                
-    pub root_entry_count: u16,        
-    pub total_sectors_16: u16,        
-    pub media_descriptor: u8,          
-    pub fat_size_16: u16,             
-    pub sectors_per_track: u16,     
-    pub num_heads: u16,             
-    pub hidden_sectors: u32,        
-    pub total_sectors_32: u32,     
-
-    pub fat_size_32: u32,              
-    pub ext_flags: u16,              
-    pub fs_version: u16,          
-    pub root_cluster: u32,            
-    pub fs_info_sector: u16,           
-    pub backup_boot_sector: u16,       
-    pub reserved: [u8; 12],           
-    pub drive_num: u8,                 
-    pub reserved_1: u8,                
-    pub boot_signature: u8,           
-    pub volume_id: u32,                
-    pub volume_label: [u8; 11],        
-    pub fs_type: [u8; 8],              
+    pub bytes_per_sector: u16,
+    pub sectors_per_cluster: u8,
+    pub reserved_sector_count: u16,
+    pub num_fats: u8,
+    pub root_entry_count: u16,
+    pub total_sectors_16: u16,
+    pub media_descriptor: u8,
+    pub fat_size_16: u16,
+    pub sectors_per_track: u16,
+    pub num_heads: u16,
+    pub hidden_sectors: u32,
+    pub total_sectors_32: u32,
+
+    pub fat_size_32: u32,
+    pub ext_flags: u16,
+    pub fs_version: u16,
+    pub root_cluster: u32,
+    pub fs_info_sector: u16,
+    pub backup_boot_sector: u16,
+    pub reserved: [u8; 12],
+    pub drive_num: u8,
+    pub reserved_1: u8,
+    pub boot_signature: u8,
+    pub volume_id: u32,
+    pub volume_label: [u8; 11],
+    pub fs_type: [u8; 8],
 }
 
-#[repr(packed)]
+#[repr(C, packed)]
 #[allow(dead_code)]
 pub struct BootSector {
-    pub jmp_boot: [u8; 3],     
-    pub oem_name: [u8; 8],          
-    pub bpb: BiosParameterBlock,    
-    _padding: [u8; 420],            
-    pub boot_signature: u16,        
+    pub jmp_boot: [u8; 3],
+    pub oem_name: [u8; 8],
+    pub bpb: BiosParameterBlock,
+    _padding: [u8; 420],
+    pub boot_signature: u16,
 }
 
+/// Géométrie calculée au montage, pour le diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    pub first_fat_sector: u32,
+    pub fat_sectors: u32,
+    pub first_data_sector: u32,
+    pub cluster_count: u32,
+    pub sectors_per_cluster: u32,
+    pub fat_size: u32,
+    pub root_cluster: u32,
+}
 
 pub struct FsInfo {
     pub bytes_per_sector: u32,
@@ -283,38 +1049,76 @@ pub struct FsInfo {
     pub first_fat_sector: u32,
     pub first_data_sector: u32,
     pub cluster_count: u32,
+    /// `None` si le secteur de démarrage désigne un emplacement FSInfo invalide.
+    pub fs_info_sector: Option<u32>,
+    /// `None` si le volume n'a pas de copie de secours du secteur de démarrage.
+    pub backup_boot_sector: Option<u32>,
+    pub hidden_sectors: u32,
+}
+
+#[repr(C, packed)]
+#[allow(dead_code)]
+pub struct FsInfoSector {
+    pub lead_signature: u32,
+    pub reserved_1: [u8; 480],
+    pub struct_signature: u32,
+    pub free_count: u32,
+    pub next_free: u32,
+    pub reserved_2: [u8; 12],
+    pub trail_signature: u32,
 }
 
+pub const FS_INFO_LEAD_SIGNATURE: u32 = 0x41615252;
+pub const FS_INFO_STRUCT_SIGNATURE: u32 = 0x61417272;
+pub const FS_INFO_TRAIL_SIGNATURE: u32 = 0xAA550000;
+
 /// # Safety
 /// Le slice d'entrée doit être suffisamment grand pour contenir la structure T (`slice.len() >= size_of::<T>()`)
 /// L'alignement de la structure T doit être valide dans le contexte `#[repr(packed)]` utilisé
 /// La séquence d'octets dans le slice doit représenter une valeur valide pour la structure T
-
 unsafe fn cast_slice_to_struct<T>(slice: &[u8]) -> &T {
-    &*(slice.as_ptr() as *const T)
+    unsafe { &*(slice.as_ptr() as *const T) }
 }
 
+/// # Safety
+/// Mêmes conditions que `cast_slice_to_struct`, le slice étant en plus modifiable
+unsafe fn cast_slice_to_struct_mut<T>(slice: &mut [u8]) -> &mut T {
+    unsafe { &mut *(slice.as_mut_ptr() as *mut T) }
+}
 
-#[repr(packed)]
+#[repr(C, packed)]
 pub struct DirEntry {
-    pub name: [u8; 11],       
-    pub attributes: u8,         
-    pub nt_reserved: u8,        
-    pub create_time_tenth: u8,  
-    pub create_time: u16,      
-    pub create_date: u16,      
-    pub last_access_date: u16,  
+    pub name: [u8; 11],
+    pub attributes: u8,
+    pub nt_reserved: u8,
+    pub create_time_tenth: u8,
+    pub create_time: u16,
+    pub create_date: u16,
+    pub last_access_date: u16,
     pub first_cluster_high: u16,
-    pub write_time: u16,        
-    pub write_date: u16,      
-    pub first_cluster_low: u16, 
-    pub file_size: u32,      
+    pub write_time: u16,
+    pub write_date: u16,
+    pub first_cluster_low: u16,
+    pub file_size: u32,
+}
+
+impl DirEntry {
+    fn is_lfn_or_volume_label(&self) -> bool {
+        self.attributes & ATTR_VOLUME_ID != 0
+    }
+
+    /// Bits réservés 0x40 et 0x80, ou `VOLUME_ID` combiné à `DIRECTORY` : l'entrée est
+    /// corrompue.
+    fn has_invalid_attributes(&self) -> bool {
+        const VOLUME_DIRECTORY: u8 = ATTR_VOLUME_ID | ATTR_DIRECTORY;
+        self.attributes & 0xC0 != 0 || self.attributes & VOLUME_DIRECTORY == VOLUME_DIRECTORY
+    }
 }
 
 pub const ATTR_READ_ONLY: u8 = 0x01;
-pub const ATTR_HIDDEN: u8    = 0x02;
-pub const ATTR_SYSTEM: u8    = 0x04;
+pub const ATTR_HIDDEN: u8 = 0x02;
+pub const ATTR_SYSTEM: u8 = 0x04;
 pub const ATTR_VOLUME_ID: u8 = 0x08;
 pub const ATTR_DIRECTORY: u8 = 0x10;
-pub const ATTR_ARCHIVE: u8   = 0x20;
-pub const ATTR_LFN: u8       = 0x0F;
+pub const ATTR_ARCHIVE: u8 = 0x20;
+pub const ATTR_LFN: u8 = 0x0F;
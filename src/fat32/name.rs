@@ -0,0 +1,147 @@
+use super::{Error, cp437};
+use alloc::string::String;
+
+/// Premier octet d'une entrée supprimée.
+const DELETED_MARKER: u8 = 0xE5;
+/// Valeur stockée à la place d'un premier octet 0xE5 légitime (octet de tête Kanji).
+const ESCAPED_DELETED_MARKER: u8 = 0x05;
+
+fn is_valid_short_name_byte(byte: u8) -> bool {
+    byte.is_ascii_uppercase()
+        || byte.is_ascii_digit()
+        || byte >= 0x80
+        || b"$%'-_@~`!(){}^#&".contains(&byte)
+}
+
+fn encode_part(part: &str, raw: &mut [u8], to_upper: fn(u8) -> u8) -> Result<(), Error> {
+    for (i, c) in part.chars().enumerate() {
+        let byte = cp437::encode(c).map(to_upper).ok_or(Error::InvalidPath)?;
+        if i == raw.len() || !is_valid_short_name_byte(byte) {
+            return Err(Error::InvalidPath);
+        }
+        raw[i] = byte;
+    }
+    Ok(())
+}
+
+/// Convertit un nom de fichier au format 8.3 tel qu'il est stocké dans une entrée de
+/// répertoire (base et extension complétées par des espaces, en majuscules).
+pub(super) fn encode_short_name(name: &str) -> Result<[u8; 11], Error> {
+    encode_name(name, cp437::to_upper)
+}
+
+/// Comme `encode_short_name`, sans replier les lettres accentuées : donne les octets d'un
+/// nom stocké en minuscules accentuées par un autre système, tel que l'affiche
+/// `decode_short_name`.
+pub(super) fn encode_stored_short_name(name: &str) -> Result<[u8; 11], Error> {
+    encode_name(name, |byte| byte.to_ascii_uppercase())
+}
+
+fn encode_name(name: &str, to_upper: fn(u8) -> u8) -> Result<[u8; 11], Error> {
+    if name == "." || name == ".." {
+        return Err(Error::InvalidPath);
+    }
+
+    let (base, extension) = match name.rsplit_once('.') {
+        Some((base, extension)) => (base, extension),
+        None => (name, ""),
+    };
+    if base.is_empty() {
+        return Err(Error::InvalidPath);
+    }
+
+    let mut raw = [b' '; 11];
+    encode_part(base, &mut raw[..8], to_upper)?;
+    encode_part(extension, &mut raw[8..], to_upper)?;
+
+    if raw[0] == DELETED_MARKER {
+        raw[0] = ESCAPED_DELETED_MARKER;
+    }
+
+    Ok(raw)
+}
+
+/// Encode le premier caractère d'un nom court, effacé lorsque l'entrée a été supprimée.
+pub(super) fn encode_first_byte(c: char) -> Result<u8, Error> {
+    let mut raw = *b" ";
+    encode_part(c.encode_utf8(&mut [0; 4]), &mut raw, cp437::to_upper)?;
+    if raw[0] == DELETED_MARKER {
+        return Ok(ESCAPED_DELETED_MARKER);
+    }
+    Ok(raw[0])
+}
+
+/// Octet `nt_reserved` : la base, l'extension, ou les deux, sont à afficher en minuscules.
+const NT_LOWERCASE_BASE: u8 = 0x08;
+const NT_LOWERCASE_EXTENSION: u8 = 0x10;
+
+/// Nom recherché dans un répertoire : comme `encode_short_name`, en acceptant aussi les
+/// entrées `.` et `..`.
+pub(super) fn encode_lookup_name(name: &str) -> Result<[u8; 11], Error> {
+    match name {
+        "." => Ok(*b".          "),
+        ".." => Ok(*b"..         "),
+        _ => encode_short_name(name),
+    }
+}
+
+/// Reconstruit le nom `BASE.EXT` d'une entrée de répertoire, en appliquant séparément à la
+/// base et à l'extension les indicateurs de minuscules de l'octet `nt_flags`.
+pub(super) fn decode_short_name(raw: &[u8; 11], nt_flags: u8) -> String {
+    let mut raw = *raw;
+    if raw[0] == ESCAPED_DELETED_MARKER {
+        raw[0] = DELETED_MARKER;
+    }
+    let base = trim_padding(&raw[..8]);
+    let extension = trim_padding(&raw[8..]);
+    let decode = |bytes: &[u8], lowercase: bool, name: &mut String| {
+        name.extend(bytes.iter().map(|&b| {
+            let b = if lowercase { b.to_ascii_lowercase() } else { b };
+            cp437::decode(b)
+        }))
+    };
+
+    let mut name = String::with_capacity(12);
+    decode(base, nt_flags & NT_LOWERCASE_BASE != 0, &mut name);
+    if !extension.is_empty() {
+        name.push('.');
+        decode(extension, nt_flags & NT_LOWERCASE_EXTENSION != 0, &mut name);
+    }
+    name
+}
+
+pub(super) const NO_VOLUME_LABEL: &[u8; 11] = b"NO NAME    ";
+
+/// Encode un nom de volume sur les 11 octets du secteur de démarrage et de l'entrée
+/// `VOLUME_ID` : en majuscules, complété par des espaces, sans point entre base et
+/// extension. Les caractères autorisés sont ceux des noms courts, plus l'espace après le
+/// premier caractère.
+pub(super) fn encode_volume_label(label: &str) -> Result<[u8; 11], Error> {
+    if label.is_empty() || label.chars().count() > 11 || label.starts_with(' ') {
+        return Err(Error::InvalidVolumeLabel);
+    }
+    let mut encoded = [b' '; 11];
+    for (dst, src) in encoded.iter_mut().zip(label.chars()) {
+        let byte = cp437::encode(src)
+            .map(cp437::to_upper)
+            .ok_or(Error::InvalidVolumeLabel)?;
+        if byte != b' ' && !is_valid_short_name_byte(byte) {
+            return Err(Error::InvalidVolumeLabel);
+        }
+        *dst = byte;
+    }
+    Ok(encoded)
+}
+
+/// Décode un champ texte complété par des espaces (nom du volume, nom OEM).
+pub(super) fn decode_padded(raw: &[u8]) -> String {
+    trim_padding(raw)
+        .iter()
+        .map(|&b| cp437::decode(b))
+        .collect()
+}
+
+fn trim_padding(bytes: &[u8]) -> &[u8] {
+    let len = bytes.iter().rposition(|&b| b != b' ').map_or(0, |i| i + 1);
+    &bytes[..len]
+}
@@ -0,0 +1,138 @@
+use super::{Disk, Error, Fat32, FileInfo, name};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Entrée supprimée d'un répertoire, renvoyée par `Fat32::deleted_entries`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeletedEntry {
+    /// Position de l'entrée dans le répertoire, en entrées de 32 octets depuis le début.
+    pub slot: usize,
+    /// Nom reconstruit ; son premier caractère, effacé par la suppression, est remplacé
+    /// par `?`.
+    pub name: String,
+    pub is_directory: bool,
+    pub size: u32,
+    pub start_cluster: u32,
+    pub attributes: u8,
+    /// Le premier cluster est encore libre : le contenu n'a peut-être pas été écrasé.
+    pub recoverable: bool,
+}
+
+impl<D: Disk> Fat32<D> {
+    /// Liste les entrées supprimées du répertoire `path` qui ne sont pas des fragments de
+    /// nom long.
+    pub fn deleted_entries(&self, path: &str) -> Result<Vec<DeletedEntry>, Error> {
+        let cluster = self.directory_cluster(path)?;
+        let mut deleted = Vec::new();
+        let mut slot = 0;
+
+        self.scan_directory(cluster, |entry, _| {
+            if entry.name[0] == 0x00 {
+                return Some(());
+            }
+            slot += 1;
+            if entry.name[0] != 0xE5 || entry.is_lfn_or_volume_label() {
+                return None;
+            }
+
+            let info = FileInfo::from_entry(entry, self.info.root_cluster);
+            let mut name = String::from("?");
+            name.extend(
+                name::decode_short_name(&entry.name, entry.nt_reserved)
+                    .chars()
+                    .skip(1),
+            );
+            deleted.push(DeletedEntry {
+                slot: slot - 1,
+                name,
+                is_directory: info.is_directory,
+                size: info.size,
+                start_cluster: info.start_cluster,
+                attributes: info.attributes,
+                recoverable: false,
+            });
+            None
+        })?;
+
+        for entry in &mut deleted {
+            entry.recoverable = entry.start_cluster == 0
+                || (entry.start_cluster < self.info.cluster_count + 2
+                    && self.get_fat_entry(entry.start_cluster)? == 0);
+        }
+        Ok(deleted)
+    }
+
+    /// Restaure l'entrée supprimée `slot` du répertoire `dir` (voir `DeletedEntry::slot`)
+    /// en lui redonnant `first_char` pour premier caractère.
+    ///
+    /// La récupération est approximative : la chaîne d'origine n'étant plus connue, elle est
+    /// reconstruite avec les clusters consécutifs qui suivent le premier, en nombre suffisant
+    /// pour la taille enregistrée. Si l'un d'eux a été réattribué, rien n'est modifié et
+    /// l'appel échoue avec `Error::ClustersReused`.
+    pub fn undelete(&mut self, dir: &str, slot: usize, first_char: char) -> Result<(), Error> {
+        let dir_cluster = self.directory_cluster(dir)?;
+        let mut index = 0;
+        let found = self.scan_directory(dir_cluster, |entry, location| {
+            if entry.name[0] == 0x00 {
+                return Some(None);
+            }
+            index += 1;
+            if index - 1 != slot {
+                return None;
+            }
+            if entry.name[0] != 0xE5 || entry.is_lfn_or_volume_label() {
+                return Some(None);
+            }
+            let info = FileInfo::from_entry(entry, self.info.root_cluster);
+            Some(Some((info, entry.name, location)))
+        })?;
+        let Some((info, mut short_name, location)) = found.flatten() else {
+            return Err(Error::FileNotFound);
+        };
+
+        short_name[0] = name::encode_first_byte(first_char)?;
+        if self.find_in_directory(dir_cluster, &short_name)?.is_some() {
+            return Err(Error::AlreadyExists);
+        }
+
+        let count = if info.is_directory {
+            1
+        } else {
+            self.clusters_needed(info.size as u64)
+        };
+        if count > 0 {
+            self.relink_chain(info.start_cluster, count)?;
+        }
+        let result = self.modify_entry(location, |entry| entry.name[0] = short_name[0]);
+        if result.is_err() && count > 0 {
+            self.release_chain(info.start_cluster);
+        }
+        result
+    }
+
+    /// Rattache les clusters `first..first + count`, qui doivent tous être libres.
+    fn relink_chain(&mut self, first: u32, count: u32) -> Result<(), Error> {
+        if first < 2 {
+            return Err(Error::InvalidFat32Structure);
+        }
+        let last = first
+            .checked_add(count - 1)
+            .filter(|&last| last < self.info.cluster_count + 2)
+            .ok_or(Error::ClustersReused)?;
+        for cluster in first..=last {
+            if self.get_fat_entry(cluster)? != 0 {
+                return Err(Error::ClustersReused);
+            }
+        }
+
+        for cluster in first..last {
+            self.set_fat_entry(cluster, cluster + 1)?;
+        }
+        self.set_fat_entry(last, self.options.end_of_chain)?;
+        if let Some(free_count) = &mut self.free_count {
+            *free_count = free_count.saturating_sub(count);
+        }
+        self.fs_info_dirty = true;
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use super::{Disk, Error, SECTOR_SIZE};
+
+/// Disque en lecture seule sur une image en mémoire, par exemple incluse dans le binaire
+/// avec `include_bytes!`. Les octets au-delà du dernier secteur complet sont ignorés.
+pub struct SliceDisk<'a> {
+    image: &'a [u8],
+}
+
+impl<'a> SliceDisk<'a> {
+    pub fn new(image: &'a [u8]) -> Self {
+        SliceDisk { image }
+    }
+}
+
+impl Disk for SliceDisk<'_> {
+    fn read_sector(&self, sector_lba: u32, buffer: &mut [u8]) -> Result<(), Error> {
+        if sector_lba >= self.sector_count() {
+            return Err(Error::IoError);
+        }
+        let start = sector_lba as usize * SECTOR_SIZE;
+        buffer[..SECTOR_SIZE].copy_from_slice(&self.image[start..start + SECTOR_SIZE]);
+        Ok(())
+    }
+
+    fn write_sector(&mut self, _sector_lba: u32, _buffer: &[u8]) -> Result<(), Error> {
+        Err(Error::ReadOnly)
+    }
+
+    fn sector_count(&self) -> u32 {
+        u32::try_from(self.image.len() / SECTOR_SIZE).unwrap_or(u32::MAX)
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+}
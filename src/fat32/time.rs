@@ -0,0 +1,61 @@
+use core::fmt;
+
+/// Date et heure locales, telles que FAT les enregistre (années 1980 à 2107). Seule
+/// l'heure de création conserve les secondes impaires et les centièmes de seconde.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timestamp {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub millisecond: u16,
+}
+
+impl Timestamp {
+    /// Décode une date et une heure d'entrée de répertoire ; `tenth` est le complément en
+    /// centièmes de seconde de l'heure de création (0 pour les autres). Une date nulle
+    /// indique une entrée non horodatée.
+    pub(super) fn from_fat(date: u16, time: u16, tenth: u8) -> Option<Self> {
+        if date == 0 {
+            return None;
+        }
+        let tenth = tenth.min(199);
+        Some(Timestamp {
+            year: 1980 + (date >> 9),
+            month: (date >> 5 & 0x0F) as u8,
+            day: (date & 0x1F) as u8,
+            hour: (time >> 11) as u8,
+            minute: (time >> 5 & 0x3F) as u8,
+            second: (time & 0x1F) as u8 * 2 + tenth / 100,
+            millisecond: (tenth % 100) as u16 * 10,
+        })
+    }
+
+    /// Date d'une entrée de répertoire.
+    pub(super) fn fat_date(&self) -> u16 {
+        (self.year.clamp(1980, 2107) - 1980) << 9 | (self.month as u16) << 5 | self.day as u16
+    }
+
+    /// Heure d'une entrée de répertoire, à deux secondes près.
+    pub(super) fn fat_time(&self) -> u16 {
+        (self.hour as u16) << 11 | (self.minute as u16) << 5 | (self.second / 2) as u16
+    }
+
+    /// Complément de `fat_time` en centièmes de seconde, pour l'heure de création.
+    pub(super) fn fat_time_tenth(&self) -> u8 {
+        (self.second % 2) * 100 + (self.millisecond.min(999) / 10) as u8
+    }
+}
+
+/// Source de l'heure courante utilisée pour horodater les entrées.
+pub trait Clock {
+    fn now(&self) -> Timestamp;
+}
+
+impl fmt::Debug for dyn Clock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Clock")
+    }
+}
@@ -0,0 +1,135 @@
+use super::{Disk, Error, Fat32, FileInfo};
+use alloc::string::String;
+use alloc::vec::{IntoIter, Vec};
+
+/// Répertoire en cours de parcours ; son contenu n'est lu qu'au premier passage.
+struct Frame {
+    cluster: u32,
+    path: String,
+    depth: usize,
+    entries: Option<IntoIter<FileInfo>>,
+}
+
+/// Parcours en profondeur d'une arborescence, renvoyé par `Fat32::walk_iter`.
+pub struct WalkIter<'a, D: Disk> {
+    fs: &'a Fat32<D>,
+    stack: Vec<Frame>,
+    error: Option<Error>,
+}
+
+impl<D: Disk> Iterator for WalkIter<'_, D> {
+    type Item = Result<(String, FileInfo), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.error.take() {
+            return Some(Err(error));
+        }
+
+        loop {
+            let frame = self.stack.last_mut()?;
+            let entries = match &mut frame.entries {
+                Some(entries) => entries,
+                None => {
+                    // Les enfants de ce répertoire dépasseraient la profondeur autorisée
+                    if frame.depth >= self.fs.options.max_path_depth as usize {
+                        self.stack.pop();
+                        return Some(Err(Error::PathTooDeep));
+                    }
+                    match self.fs.read_directory(frame.cluster) {
+                        Ok(entries) => frame.entries.insert(entries.into_iter()),
+                        Err(error) => {
+                            self.stack.pop();
+                            return Some(Err(error));
+                        }
+                    }
+                }
+            };
+
+            let Some(entry) = entries.next() else {
+                self.stack.pop();
+                continue;
+            };
+            if entry.name == "." || entry.name == ".." {
+                continue;
+            }
+
+            let mut path = frame.path.clone();
+            path.push('/');
+            path.push_str(&entry.name);
+            if entry.is_directory {
+                let depth = frame.depth + 1;
+                self.stack.push(Frame {
+                    cluster: entry.start_cluster,
+                    path: path.clone(),
+                    depth,
+                    entries: None,
+                });
+            }
+            return Some(Ok((path, entry)));
+        }
+    }
+}
+
+impl<D: Disk> Fat32<D> {
+    /// Parcourt paresseusement l'arborescence sous `root` et renvoie chaque entrée avec son
+    /// chemin complet.
+    pub fn walk_iter(&self, root: &str) -> WalkIter<'_, D> {
+        let mut walk = WalkIter {
+            fs: self,
+            stack: Vec::new(),
+            error: None,
+        };
+        match self.directory_cluster(root) {
+            Ok(cluster) => {
+                let mut path = String::new();
+                let mut depth = 0;
+                for part in root.split('/').filter(|part| !part.is_empty()) {
+                    path.push('/');
+                    path.push_str(part);
+                    depth += 1;
+                }
+                walk.stack.push(Frame {
+                    cluster,
+                    path,
+                    depth,
+                    entries: None,
+                });
+            }
+            Err(error) => walk.error = Some(error),
+        }
+        walk
+    }
+
+    /// Renvoie toutes les entrées sous `root` avec leur chemin complet.
+    pub fn walk(&self, root: &str) -> Result<Vec<(String, FileInfo)>, Error> {
+        self.walk_iter(root).collect()
+    }
+
+    /// Espace occupé sur le disque par `path` et tout ce qu'il contient, en octets : les
+    /// clusters alloués sont comptés en entier, répertoires compris.
+    pub fn subtree_size(&self, path: &str) -> Result<u64, Error> {
+        let clusters = match self.directory_cluster(path) {
+            Ok(cluster) => {
+                let mut clusters = self.chain_length(cluster)? as u64;
+                for entry in self.walk_iter(path) {
+                    let (_, info) = entry?;
+                    clusters += self.allocated_clusters(&info)?;
+                }
+                clusters
+            }
+            Err(Error::NotADirectory) => {
+                let file = self.find_entry(path, self.info.root_cluster)?;
+                self.allocated_clusters(&file)?
+            }
+            Err(error) => return Err(error),
+        };
+        Ok(clusters * self.cluster_size() as u64)
+    }
+
+    fn allocated_clusters(&self, info: &FileInfo) -> Result<u64, Error> {
+        if info.start_cluster == 0 {
+            return Ok(0);
+        }
+        Ok(self.chain_length(info.start_cluster)? as u64)
+    }
+}
@@ -0,0 +1,974 @@
+use super::{
+    ATTR_ARCHIVE, ATTR_DIRECTORY, ATTR_HIDDEN, ATTR_READ_ONLY, ATTR_SYSTEM, ATTR_VOLUME_ID,
+    BootSector, DirEntry, Disk, EntryLocation, Error, FAT32_ENTRY_MASK, FS_INFO_LEAD_SIGNATURE,
+    FS_INFO_STRUCT_SIGNATURE, FS_INFO_TRAIL_SIGNATURE, Fat32, FileInfo, FsInfoSector, SECTOR_SIZE,
+    Timestamp, cast_slice_to_struct_mut, io_at, is_end_of_chain, is_root_path, name,
+};
+use alloc::vec;
+use core::mem::size_of;
+
+const ZERO_SECTOR: [u8; SECTOR_SIZE] = [0; SECTOR_SIZE];
+
+fn new_entry(name: [u8; 11], attributes: u8, first_cluster: u32, size: u32) -> [u8; 32] {
+    let mut raw = [0u8; size_of::<DirEntry>()];
+    let entry = unsafe { cast_slice_to_struct_mut::<DirEntry>(&mut raw) };
+    entry.name = name;
+    entry.attributes = attributes;
+    entry.first_cluster_high = (first_cluster >> 16) as u16;
+    entry.first_cluster_low = first_cluster as u16;
+    entry.file_size = size;
+    raw
+}
+
+/// Options de `Fat32::delete_file_with_options`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DeleteOptions {
+    /// Remplit les clusters du fichier de zéros avant de les libérer.
+    pub scrub: bool,
+}
+
+/// Entrée `VOLUME_ID` du répertoire racine, sans cluster ni taille.
+pub(super) fn volume_label_entry(label: [u8; 11]) -> [u8; 32] {
+    new_entry(label, ATTR_VOLUME_ID, 0, 0)
+}
+
+impl<D: Disk> Fat32<D> {
+    pub(super) fn now(&self) -> Option<Timestamp> {
+        self.options.clock.map(|clock| clock.now())
+    }
+
+    /// Comme `new_entry`, avec les dates de création et de modification de l'horloge du
+    /// volume.
+    fn stamped_entry(
+        &self,
+        name: [u8; 11],
+        attributes: u8,
+        first_cluster: u32,
+        size: u32,
+    ) -> [u8; 32] {
+        let mut raw = new_entry(name, attributes, first_cluster, size);
+        if let Some(now) = self.now() {
+            let entry = unsafe { cast_slice_to_struct_mut::<DirEntry>(&mut raw) };
+            entry.create_time_tenth = now.fat_time_tenth();
+            entry.create_time = now.fat_time();
+            entry.create_date = now.fat_date();
+            entry.last_access_date = now.fat_date();
+            entry.write_time = now.fat_time();
+            entry.write_date = now.fat_date();
+        }
+        raw
+    }
+
+    /// Lit un secteur dans le tampon de travail, le modifie avec `f` puis le réécrit.
+    fn update_sector<T>(
+        &mut self,
+        lba: u32,
+        f: impl FnOnce(&mut [u8; SECTOR_SIZE]) -> T,
+    ) -> Result<T, Error> {
+        if self.options.read_only {
+            return Err(Error::ReadOnly);
+        }
+        self.invalidate_fat_cache(lba);
+        let scratch = self.scratch.get_mut();
+        self.disk.read_sector(lba, scratch).map_err(io_at(lba))?;
+        let result = f(scratch);
+        self.disk.write_sector(lba, scratch).map_err(io_at(lba))?;
+        Ok(result)
+    }
+
+    /// Écrit un secteur, sauf sur un volume monté en lecture seule.
+    fn write_sector(&mut self, lba: u32, buffer: &[u8]) -> Result<(), Error> {
+        if self.options.read_only {
+            return Err(Error::ReadOnly);
+        }
+        self.invalidate_fat_cache(lba);
+        self.disk.write_sector(lba, buffer).map_err(io_at(lba))
+    }
+
+    fn invalidate_fat_cache(&mut self, lba: u32) {
+        let cache = self.fat_cache.get_mut();
+        if cache.as_ref().is_some_and(|(cached, _)| *cached == lba) {
+            *cache = None;
+        }
+    }
+
+    /// Recopie le nombre de clusters libres et l'indice du prochain cluster libre tenus en
+    /// mémoire dans le secteur FSInfo, s'ils ont changé depuis la dernière écriture.
+    pub fn sync_fs_info(&mut self) -> Result<(), Error> {
+        let Some(fs_info_sector) = self.info.fs_info_sector else {
+            return Ok(());
+        };
+        if !self.fs_info_dirty || self.disk.is_read_only() {
+            return Ok(());
+        }
+        let free_count = self.free_count.unwrap_or(0xFFFFFFFF);
+        let next_free = self.next_free;
+
+        self.update_sector(fs_info_sector, |buffer| {
+            let fs_info = unsafe { cast_slice_to_struct_mut::<FsInfoSector>(buffer) };
+            fs_info.lead_signature = FS_INFO_LEAD_SIGNATURE;
+            fs_info.struct_signature = FS_INFO_STRUCT_SIGNATURE;
+            fs_info.free_count = free_count;
+            fs_info.next_free = next_free;
+            fs_info.trail_signature = FS_INFO_TRAIL_SIGNATURE;
+        })?;
+        self.fs_info_dirty = false;
+        Ok(())
+    }
+
+    /// Recopie dans les autres FAT les secteurs de la première FAT modifiés depuis la
+    /// dernière synchronisation.
+    pub fn sync_fats(&mut self) -> Result<(), Error> {
+        let Some((first, last)) = self.unmirrored_fat_sectors else {
+            return Ok(());
+        };
+        if self.options.read_only {
+            return Err(Error::ReadOnly);
+        }
+
+        let scratch = self.scratch.get_mut();
+        for sector in first..=last {
+            let lba = self.info.first_fat_sector + sector;
+            self.disk.read_sector(lba, scratch).map_err(io_at(lba))?;
+            for fat in 1..self.info.num_fats {
+                let mirror = lba + fat * self.info.fat_size;
+                self.disk
+                    .write_sector(mirror, scratch)
+                    .map_err(io_at(mirror))?;
+            }
+        }
+        self.unmirrored_fat_sectors = None;
+        Ok(())
+    }
+
+    /// Écrit sur le disque les informations tenues en mémoire. Rien n'est écrit si elles
+    /// n'ont pas changé, ce qui permet de vider un volume sur un disque en lecture seule.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        if self.options.read_only {
+            return Ok(());
+        }
+        self.sync_fats()?;
+        self.sync_fs_info()
+    }
+
+    /// Démonte le volume et rend le disque, avec le résultat des dernières écritures : le
+    /// disque est rendu même si elles échouent.
+    pub fn unmount(mut self) -> (D, Result<(), Error>) {
+        let result = self.flush();
+        (self.disk, result)
+    }
+
+    pub(super) fn set_fat_entry(&mut self, cluster: u32, value: u32) -> Result<(), Error> {
+        if cluster < 2 || cluster >= self.info.cluster_count + 2 {
+            return Err(Error::InvalidFat32Structure);
+        }
+
+        let fat_entry_offset = cluster * 4;
+        let sector_in_fat = fat_entry_offset / self.info.bytes_per_sector;
+        let entry_in_sector = (fat_entry_offset % self.info.bytes_per_sector) as usize;
+
+        // Chaque copie de la FAT est mise à jour, en conservant les 4 bits de poids fort ;
+        // en mode différé, seule la première l'est et le secteur est noté pour `sync_fats`
+        let fats = if self.options.deferred_fat_mirror {
+            self.unmirrored_fat_sectors = Some(match self.unmirrored_fat_sectors {
+                Some((first, last)) => (first.min(sector_in_fat), last.max(sector_in_fat)),
+                None => (sector_in_fat, sector_in_fat),
+            });
+            1
+        } else {
+            self.info.num_fats
+        };
+        for fat in 0..fats {
+            let lba = self.info.first_fat_sector + fat * self.info.fat_size + sector_in_fat;
+            self.update_sector(lba, |buffer| {
+                let old = u32::from_le_bytes([
+                    buffer[entry_in_sector],
+                    buffer[entry_in_sector + 1],
+                    buffer[entry_in_sector + 2],
+                    buffer[entry_in_sector + 3],
+                ]);
+                let new = (old & !FAT32_ENTRY_MASK) | (value & FAT32_ENTRY_MASK);
+                buffer[entry_in_sector..entry_in_sector + 4].copy_from_slice(&new.to_le_bytes());
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Réserve un cluster libre et le marque comme fin de chaîne.
+    pub(super) fn allocate_cluster(&mut self) -> Result<u32, Error> {
+        let cluster_count = self.info.cluster_count;
+        let start = if (2..cluster_count + 2).contains(&self.next_free) {
+            self.next_free
+        } else {
+            2
+        };
+
+        for i in 0..cluster_count {
+            let cluster = 2 + (start - 2 + i) % cluster_count;
+            if self.get_fat_entry(cluster)? == 0 {
+                self.set_fat_entry(cluster, self.options.end_of_chain)?;
+                self.next_free = cluster + 1;
+                if let Some(free_count) = &mut self.free_count {
+                    *free_count = free_count.saturating_sub(1);
+                }
+                self.fs_info_dirty = true;
+                return Ok(cluster);
+            }
+        }
+
+        Err(Error::DiskFull)
+    }
+
+    /// Réserve une chaîne de `count` clusters et renvoie le premier (0 si `count` est nul).
+    /// Si l'allocation échoue en cours de route, les clusters déjà réservés sont libérés.
+    fn allocate_chain(&mut self, count: u32) -> Result<u32, Error> {
+        let mut first = 0;
+        let mut previous = 0;
+
+        for _ in 0..count {
+            let cluster = match self.allocate_cluster() {
+                Ok(cluster) => cluster,
+                Err(e) => {
+                    self.release_chain(first);
+                    return Err(e);
+                }
+            };
+            if previous == 0 {
+                first = cluster;
+            } else if let Err(e) = self.set_fat_entry(previous, cluster) {
+                let _ = self.set_fat_entry(cluster, 0);
+                self.release_chain(first);
+                return Err(e);
+            }
+            previous = cluster;
+        }
+
+        Ok(first)
+    }
+
+    /// Libère une chaîne en ignorant les erreurs, pour annuler une opération qui a échoué.
+    pub(super) fn release_chain(&mut self, first: u32) {
+        if first != 0 {
+            let _ = self.free_chain(first);
+        }
+    }
+
+    fn free_chain(&mut self, start_cluster: u32) -> Result<(), Error> {
+        let mut cluster = start_cluster;
+
+        for _ in 0..self.info.cluster_count {
+            let next = self.get_fat_entry(cluster)?;
+            self.set_fat_entry(cluster, 0)?;
+            if cluster < self.next_free {
+                self.next_free = cluster;
+            }
+            if let Some(free_count) = &mut self.free_count {
+                *free_count += 1;
+            }
+            self.fs_info_dirty = true;
+            if !(2..0x0FFFFFF8).contains(&next) {
+                return Ok(());
+            }
+            cluster = next;
+        }
+
+        Err(Error::InvalidFat32Structure)
+    }
+
+    fn last_cluster(&self, start_cluster: u32) -> Result<u32, Error> {
+        let mut cluster = start_cluster;
+
+        for _ in 0..self.info.cluster_count {
+            let next = self.get_fat_entry(cluster)?;
+            if is_end_of_chain(next) {
+                return Ok(cluster);
+            }
+            cluster = next;
+        }
+
+        Err(Error::InvalidFat32Structure)
+    }
+
+    /// Ramène la chaîne qui commence à `first_cluster` (0 si elle est vide) à `count`
+    /// clusters, en libérant la fin ou en l'allongeant, et renvoie son premier cluster.
+    fn resize_chain(&mut self, first_cluster: u32, count: u32) -> Result<u32, Error> {
+        if count == 0 {
+            if first_cluster >= 2 {
+                self.free_chain(first_cluster)?;
+            }
+            return Ok(0);
+        }
+        if first_cluster < 2 {
+            return self.allocate_chain(count);
+        }
+
+        let mut cluster = first_cluster;
+        for length in 1..count {
+            let next = self.get_fat_entry(cluster)?;
+            if is_end_of_chain(next) {
+                let extension = self.allocate_chain(count - length)?;
+                if let Err(e) = self.set_fat_entry(cluster, extension) {
+                    self.release_chain(extension);
+                    return Err(e);
+                }
+                return Ok(first_cluster);
+            }
+            cluster = next;
+        }
+
+        let next = self.get_fat_entry(cluster)?;
+        if !is_end_of_chain(next) {
+            self.set_fat_entry(cluster, self.options.end_of_chain)?;
+            self.free_chain(next)?;
+        }
+        Ok(first_cluster)
+    }
+
+    /// Remplit de zéros la chaîne qui commence à `first_cluster`, de l'octet `offset`
+    /// jusqu'à sa fin.
+    fn zero_from(&mut self, first_cluster: u32, offset: u32) -> Result<(), Error> {
+        let cluster_size = self.cluster_size();
+        let mut cluster = match self.cluster_at(first_cluster, offset / cluster_size) {
+            Ok(cluster) => cluster,
+            Err(Error::OutOfRange) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        let mut within = offset % cluster_size;
+        for _ in 0..self.info.cluster_count {
+            let lba = self.cluster_to_lba(cluster)?;
+            let mut sector = within / SECTOR_SIZE as u32;
+            let byte = within as usize % SECTOR_SIZE;
+            if byte != 0 {
+                self.update_sector(lba + sector, |buffer| buffer[byte..].fill(0))?;
+                sector += 1;
+            }
+            for i in sector..self.info.sectors_per_cluster {
+                self.write_sector(lba + i, &ZERO_SECTOR)?;
+            }
+
+            within = 0;
+            cluster = self.get_fat_entry(cluster)?;
+            if is_end_of_chain(cluster) {
+                return Ok(());
+            }
+        }
+
+        Err(Error::InvalidFat32Structure)
+    }
+
+    fn zero_cluster(&mut self, cluster: u32) -> Result<(), Error> {
+        let lba = self.cluster_to_lba(cluster)?;
+        for i in 0..self.info.sectors_per_cluster {
+            self.write_sector(lba + i, &ZERO_SECTOR)?;
+        }
+        Ok(())
+    }
+
+    /// Remplace les 32 octets de l'entrée située à `offset` dans le secteur `lba`. Le
+    /// secteur est relu pour que les entrées voisines restent intactes ; toutes les
+    /// modifications d'une entrée existante passent par ici.
+    fn update_entry(&mut self, lba: u32, offset: usize, bytes: &[u8; 32]) -> Result<(), Error> {
+        if !offset.is_multiple_of(bytes.len()) || offset >= SECTOR_SIZE {
+            return Err(Error::InvalidFat32Structure);
+        }
+        self.update_sector(lba, |buffer| {
+            buffer[offset..offset + bytes.len()].copy_from_slice(bytes)
+        })
+    }
+
+    pub(super) fn modify_entry(
+        &mut self,
+        location: EntryLocation,
+        modify: impl FnOnce(&mut DirEntry),
+    ) -> Result<(), Error> {
+        let mut raw = self.read_entry(location)?;
+        modify(unsafe { cast_slice_to_struct_mut::<DirEntry>(&mut raw) });
+        self.update_entry(location.lba, location.offset, &raw)
+    }
+
+    /// Écrit une nouvelle entrée dans le premier emplacement libre du répertoire, en
+    /// l'agrandissant d'un cluster si nécessaire.
+    fn insert_entry(&mut self, dir_cluster: u32, raw: &[u8; 32]) -> Result<EntryLocation, Error> {
+        let free_slot = self.scan_directory(dir_cluster, |entry, location| {
+            (entry.name[0] == 0x00 || entry.name[0] == 0xE5).then_some(location)
+        })?;
+
+        if let Some(location) = free_slot {
+            self.update_entry(location.lba, location.offset, raw)?;
+            return Ok(location);
+        }
+
+        // Répertoire plein : un cluster est ajouté, puis retiré si l'entrée n'y est pas
+        // écrite
+        let last = self.last_cluster(dir_cluster)?;
+        let cluster = self.allocate_cluster()?;
+        let location = EntryLocation {
+            lba: self.cluster_to_lba(cluster)?,
+            offset: 0,
+        };
+        let result = self
+            .zero_cluster(cluster)
+            .and_then(|()| self.set_fat_entry(last, cluster))
+            .and_then(|()| self.update_entry(location.lba, location.offset, raw));
+        if let Err(e) = result {
+            let _ = self.set_fat_entry(last, self.options.end_of_chain);
+            self.release_chain(cluster);
+            return Err(e);
+        }
+        Ok(location)
+    }
+
+    /// Change le nom du volume dans le secteur de démarrage, sa copie de secours et
+    /// l'entrée `VOLUME_ID` du répertoire racine, créée si elle n'existe pas encore.
+    pub fn set_volume_label(&mut self, label: &str) -> Result<(), Error> {
+        let label = name::encode_volume_label(label)?;
+
+        for lba in core::iter::once(0).chain(self.info.backup_boot_sector) {
+            self.update_sector(lba, |buffer| {
+                let boot_sector = unsafe { cast_slice_to_struct_mut::<BootSector>(buffer) };
+                boot_sector.bpb.volume_label = label;
+            })?;
+        }
+
+        let existing = self.scan_directory(self.info.root_cluster, |entry, location| {
+            if entry.name[0] == 0x00 {
+                return Some(None);
+            }
+            (entry.name[0] != 0xE5 && entry.attributes == ATTR_VOLUME_ID).then_some(Some(location))
+        })?;
+        match existing.flatten() {
+            Some(location) => self.modify_entry(location, |entry| entry.name = label),
+            None => self
+                .insert_entry(self.info.root_cluster, &volume_label_entry(label))
+                .map(|_| ()),
+        }
+    }
+
+    /// Modifie les attributs `READ_ONLY`, `HIDDEN`, `SYSTEM` et `ARCHIVE` d'une entrée.
+    pub fn set_attributes(&mut self, path: &str, attributes: u8) -> Result<(), Error> {
+        const MODIFIABLE: u8 = ATTR_READ_ONLY | ATTR_HIDDEN | ATTR_SYSTEM | ATTR_ARCHIVE;
+
+        let (_, location) = self.locate(path)?;
+        self.modify_entry(location, |entry| {
+            entry.attributes = (entry.attributes & !MODIFIABLE) | (attributes & MODIFIABLE);
+        })
+    }
+
+    pub fn delete_file(&mut self, path: &str) -> Result<(), Error> {
+        self.delete_file_with_options(path, DeleteOptions::default())
+    }
+
+    /// Supprime un fichier. Un fichier en lecture seule doit d'abord perdre son attribut
+    /// `READ_ONLY`.
+    pub fn delete_file_with_options(
+        &mut self,
+        path: &str,
+        options: DeleteOptions,
+    ) -> Result<(), Error> {
+        let (info, location) = self.locate(path)?;
+        if info.is_directory {
+            return Err(Error::IsADirectory);
+        }
+        if info.attributes & ATTR_READ_ONLY != 0 {
+            return Err(Error::ReadOnly);
+        }
+
+        // Les clusters sont effacés tant qu'ils appartiennent encore au fichier, pour
+        // qu'aucune allocation ne puisse les récupérer avant la fin de l'effacement
+        if options.scrub && info.start_cluster >= 2 {
+            let mut cluster = info.start_cluster;
+            for _ in 0..self.info.cluster_count {
+                self.zero_cluster(cluster)?;
+                cluster = self.get_fat_entry(cluster)?;
+                if !(2..0x0FFFFFF8).contains(&cluster) {
+                    break;
+                }
+            }
+        }
+
+        self.modify_entry(location, |entry| entry.name[0] = 0xE5)?;
+        if info.start_cluster >= 2 {
+            self.free_chain(info.start_cluster)?;
+        }
+        Ok(())
+    }
+
+    /// Crée un répertoire vide contenant les entrées `.` et `..`.
+    pub fn create_dir(&mut self, path: &str) -> Result<(), Error> {
+        let (dir_cluster, leaf) = self.parent_of(path)?;
+        let short_name = name::encode_short_name(&leaf)?;
+
+        if self.find_named(dir_cluster, &leaf)?.is_some() {
+            return Err(Error::AlreadyExists);
+        }
+
+        let cluster = self.allocate_cluster()?;
+        if let Err(e) = self.init_directory(cluster, dir_cluster) {
+            self.release_chain(cluster);
+            return Err(e);
+        }
+
+        let raw = self.stamped_entry(short_name, ATTR_DIRECTORY, cluster, 0);
+        if let Err(e) = self.insert_entry(dir_cluster, &raw) {
+            self.release_chain(cluster);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    fn init_directory(&mut self, cluster: u32, parent_cluster: u32) -> Result<(), Error> {
+        // Le `..` d'un sous-répertoire de la racine pointe sur le cluster 0
+        let parent_cluster = if parent_cluster == self.info.root_cluster {
+            0
+        } else {
+            parent_cluster
+        };
+        let dot = self.stamped_entry(*b".          ", ATTR_DIRECTORY, cluster, 0);
+        let dot_dot = self.stamped_entry(*b"..         ", ATTR_DIRECTORY, parent_cluster, 0);
+
+        self.zero_cluster(cluster)?;
+        self.update_sector(self.cluster_to_lba(cluster)?, |buffer| {
+            buffer[..32].copy_from_slice(&dot);
+            buffer[32..64].copy_from_slice(&dot_dot);
+        })
+    }
+
+    /// Crée un fichier vide.
+    pub fn create_file(&mut self, path: &str) -> Result<(), Error> {
+        let (dir_cluster, leaf) = self.parent_of(path)?;
+        let short_name = name::encode_short_name(&leaf)?;
+
+        if self.find_named(dir_cluster, &leaf)?.is_some() {
+            return Err(Error::AlreadyExists);
+        }
+
+        let raw = self.stamped_entry(short_name, ATTR_ARCHIVE, 0, 0);
+        self.insert_entry(dir_cluster, &raw)?;
+        Ok(())
+    }
+
+    /// Crée un fichier vide s'il n'existe pas, sinon met à jour sa date de modification.
+    pub fn touch(&mut self, path: &str) -> Result<(), Error> {
+        match self.locate(path) {
+            Ok((_, location)) => {
+                let Some(now) = self.now() else {
+                    return Ok(());
+                };
+                self.modify_entry(location, |entry| {
+                    entry.write_time = now.fat_time();
+                    entry.write_date = now.fat_date();
+                })
+            }
+            Err(Error::FileNotFound) => self.create_file(path),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Déplace ou renomme une entrée. Renommer une entrée en elle-même ne fait rien.
+    pub fn rename(&mut self, from: &str, to: &str) -> Result<(), Error> {
+        let (info, location) = self.locate(from)?;
+        let (dir_cluster, leaf) = self.parent_of(to)?;
+        let short_name = name::encode_short_name(&leaf)?;
+
+        if let Some((_, existing)) = self.find_named(dir_cluster, &leaf)? {
+            if existing == location {
+                return Ok(());
+            }
+            return Err(Error::AlreadyExists);
+        }
+
+        if info.is_directory {
+            // Un répertoire ne peut pas être déplacé dans sa propre arborescence
+            let mut cluster = dir_cluster;
+            for _ in 0..self.info.cluster_count {
+                if cluster == info.start_cluster {
+                    return Err(Error::InvalidPath);
+                }
+                if cluster == self.info.root_cluster {
+                    break;
+                }
+                cluster = match self.find_in_directory(cluster, b"..         ")? {
+                    Some((parent, _)) => parent.start_cluster,
+                    None => return Err(Error::InvalidFat32Structure),
+                };
+            }
+        }
+
+        let mut raw = self.read_entry(location)?;
+        raw[..11].copy_from_slice(&short_name);
+        // Les indicateurs de minuscules portaient sur l'ancien nom
+        raw[12] = 0;
+        self.insert_entry(dir_cluster, &raw)?;
+        self.modify_entry(location, |entry| entry.name[0] = 0xE5)?;
+
+        if info.is_directory {
+            let parent_cluster = if dir_cluster == self.info.root_cluster {
+                0
+            } else {
+                dir_cluster
+            };
+            let dot_dot = EntryLocation {
+                lba: self.cluster_to_lba(info.start_cluster)?,
+                offset: size_of::<DirEntry>(),
+            };
+            self.modify_entry(dot_dot, |entry| {
+                entry.first_cluster_high = (parent_cluster >> 16) as u16;
+                entry.first_cluster_low = parent_cluster as u16;
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Copie le contenu du fichier `from` dans `to`, créé ou remplacé.
+    pub fn copy_file(&mut self, from: &str, to: &str) -> Result<(), Error> {
+        let (info, _) = self.locate(from)?;
+        if info.is_directory {
+            return Err(Error::IsADirectory);
+        }
+        match self.same_file(from, to) {
+            Ok(true) => return Err(Error::SameFile),
+            Ok(false) | Err(Error::FileNotFound) => {}
+            Err(e) => return Err(e),
+        }
+
+        let data = self.read_file(&info)?;
+        self.write_file(to, &data)
+    }
+
+    /// Écrit `data` dans le fichier `path`, en le créant ou en remplaçant son contenu.
+    pub fn write_file(&mut self, path: &str, data: &[u8]) -> Result<(), Error> {
+        self.write_data(path, data, None)
+    }
+
+    /// Écrit un fichier de `total_len` octets dont le contenu est produit par `fill`,
+    /// appelé une fois par cluster avec le tampon à remplir entièrement.
+    ///
+    /// Les clusters sont réservés avant la première écriture. Si `fill` renvoie moins
+    /// d'octets que demandé, l'opération échoue avec `Error::IncompleteWrite` et le
+    /// fichier existant n'est pas modifié.
+    pub fn write_file_streaming<F: FnMut(&mut [u8]) -> Result<usize, Error>>(
+        &mut self,
+        path: &str,
+        total_len: u32,
+        fill: F,
+    ) -> Result<(), Error> {
+        self.write_entry(path, total_len, None, fill)
+    }
+
+    /// Comme `write_file`, en donnant au fichier les attributs `attributes` qu'il soit créé
+    /// ou remplacé. Seuls `READ_ONLY`, `HIDDEN`, `SYSTEM` et `ARCHIVE` sont acceptés.
+    pub fn write_file_with_attrs(
+        &mut self,
+        path: &str,
+        data: &[u8],
+        attributes: u8,
+    ) -> Result<(), Error> {
+        if attributes & !(ATTR_READ_ONLY | ATTR_HIDDEN | ATTR_SYSTEM | ATTR_ARCHIVE) != 0 {
+            return Err(Error::InvalidAttributes);
+        }
+        self.write_data(path, data, Some(attributes))
+    }
+
+    /// Écrit `data` d'un seul tenant, pour `write_file` et `write_file_with_attrs`.
+    fn write_data(&mut self, path: &str, data: &[u8], attributes: Option<u8>) -> Result<(), Error> {
+        let total_len = u32::try_from(data.len()).map_err(|_| Error::FileTooLarge)?;
+        let mut written = 0;
+
+        self.write_entry(path, total_len, attributes, |buffer| {
+            buffer.copy_from_slice(&data[written..written + buffer.len()]);
+            written += buffer.len();
+            Ok(buffer.len())
+        })
+    }
+
+    /// Écrit le fichier `path` ; sans `attributes`, un fichier remplacé garde les siens et un
+    /// nouveau fichier reçoit `ARCHIVE`.
+    fn write_entry<F: FnMut(&mut [u8]) -> Result<usize, Error>>(
+        &mut self,
+        path: &str,
+        total_len: u32,
+        attributes: Option<u8>,
+        mut fill: F,
+    ) -> Result<(), Error> {
+        if is_root_path(path) {
+            return Err(Error::IsADirectory);
+        }
+        let (dir_cluster, leaf) = self.parent_of(path)?;
+        let short_name = name::encode_short_name(&leaf)?;
+
+        let existing = self.find_named(dir_cluster, &leaf)?;
+        if let Some((info, _)) = &existing
+            && info.is_directory
+        {
+            return Err(Error::IsADirectory);
+        }
+
+        let first_cluster = self.allocate_chain(self.clusters_needed(total_len as u64))?;
+        if let Err(e) = self.fill_chain(first_cluster, total_len, &mut fill) {
+            self.release_chain(first_cluster);
+            return Err(e);
+        }
+
+        let now = self.now();
+        match existing {
+            Some((info, location)) => {
+                let result = self.modify_entry(location, |entry| {
+                    entry.first_cluster_high = (first_cluster >> 16) as u16;
+                    entry.first_cluster_low = first_cluster as u16;
+                    entry.file_size = total_len;
+                    if let Some(attributes) = attributes {
+                        entry.attributes = attributes;
+                    }
+                    if let Some(now) = now {
+                        entry.write_time = now.fat_time();
+                        entry.write_date = now.fat_date();
+                    }
+                });
+                if let Err(e) = result {
+                    self.release_chain(first_cluster);
+                    return Err(e);
+                }
+                if info.start_cluster >= 2 {
+                    self.free_chain(info.start_cluster)?;
+                }
+            }
+            None => {
+                let attributes = attributes.unwrap_or(ATTR_ARCHIVE);
+                let raw = self.stamped_entry(short_name, attributes, first_cluster, total_len);
+                if let Err(e) = self.insert_entry(dir_cluster, &raw) {
+                    self.release_chain(first_cluster);
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Ajoute `data` à la fin du fichier `path`, créé s'il n'existe pas.
+    pub fn append_file(&mut self, path: &str, data: &[u8]) -> Result<(), Error> {
+        let (info, location) = match self.locate(path) {
+            Ok(found) => found,
+            Err(Error::FileNotFound) => {
+                self.create_file(path)?;
+                self.locate(path)?
+            }
+            Err(e) => return Err(e),
+        };
+        let size = info.size;
+        self.write_entry_at(info, location, size, data)
+    }
+
+    /// Écrit `data` à la position `offset` du fichier existant `path`, sans toucher aux
+    /// octets voisins. Le fichier est allongé si nécessaire, l'espace entre l'ancienne fin
+    /// et `offset` étant rempli de zéros.
+    pub fn write_at(&mut self, path: &str, offset: u32, data: &[u8]) -> Result<(), Error> {
+        let (info, location) = self.locate(path)?;
+        self.write_entry_at(info, location, offset, data)
+    }
+
+    fn write_entry_at(
+        &mut self,
+        info: FileInfo,
+        location: EntryLocation,
+        offset: u32,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        if info.is_directory {
+            return Err(Error::IsADirectory);
+        }
+        let data_len = u32::try_from(data.len()).map_err(|_| Error::FileTooLarge)?;
+        let end = offset.checked_add(data_len).ok_or(Error::FileTooLarge)?;
+        if data.is_empty() {
+            return Ok(());
+        }
+        let new_len = end.max(info.size);
+
+        // Une chaîne préallouée peut déjà contenir les nouveaux octets
+        let current = if info.start_cluster >= 2 {
+            self.chain_length(info.start_cluster)?
+        } else {
+            0
+        };
+        let count = self.clusters_needed(new_len as u64);
+        let first_cluster = if count > current {
+            self.resize_chain(info.start_cluster, count)?
+        } else {
+            info.start_cluster
+        };
+
+        let mut result = Ok(());
+        if offset > info.size {
+            result = self.zero_from(first_cluster, info.size);
+        }
+        if result.is_ok() {
+            result = self.write_chain_at(first_cluster, offset, data);
+        }
+        if let Err(e) = result {
+            if count > current {
+                let _ = self.resize_chain(first_cluster, current);
+            }
+            return Err(e);
+        }
+
+        let now = self.now();
+        let result = self.modify_entry(location, |entry| {
+            entry.first_cluster_high = (first_cluster >> 16) as u16;
+            entry.first_cluster_low = first_cluster as u16;
+            entry.file_size = new_len;
+            if let Some(now) = now {
+                entry.write_time = now.fat_time();
+                entry.write_date = now.fat_date();
+            }
+        });
+        if result.is_err() && count > current {
+            let _ = self.resize_chain(first_cluster, current);
+        }
+        result
+    }
+
+    /// Raccourcit le fichier `path` à `new_len` octets, ou l'allonge avec des zéros.
+    pub fn truncate_file(&mut self, path: &str, new_len: u64) -> Result<(), Error> {
+        let new_len = u32::try_from(new_len).map_err(|_| Error::FileTooLarge)?;
+        let (info, location) = self.locate(path)?;
+        if info.is_directory {
+            return Err(Error::IsADirectory);
+        }
+
+        let count = self.clusters_needed(new_len as u64);
+        let current = if info.start_cluster >= 2 {
+            self.chain_length(info.start_cluster)?
+        } else {
+            0
+        };
+        let first_cluster = self.resize_chain(info.start_cluster, count)?;
+
+        let mut result = Ok(());
+        if new_len > info.size {
+            result = self.zero_from(first_cluster, info.size);
+        }
+        if result.is_ok() {
+            let now = self.now();
+            result = self.modify_entry(location, |entry| {
+                entry.first_cluster_high = (first_cluster >> 16) as u16;
+                entry.first_cluster_low = first_cluster as u16;
+                entry.file_size = new_len;
+                if let Some(now) = now {
+                    entry.write_time = now.fat_time();
+                    entry.write_date = now.fat_date();
+                }
+            });
+        }
+        // Les clusters ajoutés sont rendus ; un raccourcissement n'est pas annulé
+        if result.is_err() && count > current {
+            let _ = self.resize_chain(first_cluster, current);
+        }
+        result
+    }
+
+    /// Réserve assez de clusters pour que le fichier `path` puisse atteindre `len` octets
+    /// sans nouvelle allocation. Sa taille n'est pas modifiée ; `verify_file` signale donc
+    /// la chaîne plus longue que nécessaire.
+    pub fn preallocate(&mut self, path: &str, len: u64) -> Result<(), Error> {
+        if len > u32::MAX as u64 {
+            return Err(Error::FileTooLarge);
+        }
+        let (info, location) = self.locate(path)?;
+        if info.is_directory {
+            return Err(Error::IsADirectory);
+        }
+
+        let count = self.clusters_needed(len);
+        let current = if info.start_cluster >= 2 {
+            self.chain_length(info.start_cluster)?
+        } else {
+            0
+        };
+        if count <= current {
+            return Ok(());
+        }
+
+        let first_cluster = self.resize_chain(info.start_cluster, count)?;
+        let result = self.modify_entry(location, |entry| {
+            entry.first_cluster_high = (first_cluster >> 16) as u16;
+            entry.first_cluster_low = first_cluster as u16;
+        });
+        if result.is_err() {
+            let _ = self.resize_chain(first_cluster, current);
+        }
+        result
+    }
+
+    /// Écrit `data` dans la chaîne qui commence à `first_cluster`, à partir de l'octet
+    /// `offset` ; la chaîne doit être assez longue.
+    pub(super) fn write_chain_at(
+        &mut self,
+        first_cluster: u32,
+        offset: u32,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        let cluster_size = self.cluster_size();
+        let mut cluster = self.cluster_at(first_cluster, offset / cluster_size)?;
+
+        let mut within = offset % cluster_size;
+        let mut data = data;
+        while !data.is_empty() {
+            let lba = self.cluster_to_lba(cluster)? + within / SECTOR_SIZE as u32;
+            let byte = within as usize % SECTOR_SIZE;
+            let len = core::cmp::min(SECTOR_SIZE - byte, data.len());
+            if len == SECTOR_SIZE {
+                self.write_sector(lba, &data[..len])?;
+            } else {
+                self.update_sector(lba, |buffer| {
+                    buffer[byte..byte + len].copy_from_slice(&data[..len])
+                })?;
+            }
+
+            data = &data[len..];
+            within += len as u32;
+            if within == cluster_size && !data.is_empty() {
+                within = 0;
+                cluster = self.get_fat_entry(cluster)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_chain<F: FnMut(&mut [u8]) -> Result<usize, Error>>(
+        &mut self,
+        first_cluster: u32,
+        total_len: u32,
+        fill: &mut F,
+    ) -> Result<(), Error> {
+        let cluster_size = self.cluster_size() as usize;
+        let mut buffer = vec![0u8; cluster_size];
+        let mut cluster = first_cluster;
+        let mut remaining = total_len as usize;
+
+        while remaining > 0 {
+            let len = core::cmp::min(remaining, cluster_size);
+            buffer[len..].fill(0);
+            if fill(&mut buffer[..len])? != len {
+                return Err(Error::IncompleteWrite);
+            }
+
+            let lba = self.cluster_to_lba(cluster)?;
+            for (i, sector) in buffer
+                .chunks(SECTOR_SIZE)
+                .take(len.div_ceil(SECTOR_SIZE))
+                .enumerate()
+            {
+                self.write_sector(lba + i as u32, sector)?;
+            }
+
+            remaining -= len;
+            if remaining > 0 {
+                cluster = self.get_fat_entry(cluster)?;
+            }
+        }
+
+        Ok(())
+    }
+}
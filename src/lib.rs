@@ -9,6 +9,7 @@ extern crate alloc;
 use core::panic::PanicInfo;
 
 pub mod allocator;
+pub mod fat32;
 pub mod gdt;
 pub mod interrupts;
 pub mod memory;
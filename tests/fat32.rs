@@ -0,0 +1,2068 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(blog_os::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::{boxed::Box, collections::BTreeMap, format, string::String, vec, vec::Vec};
+use blog_os::fat32::{
+    ATTR_ARCHIVE, ATTR_DIRECTORY, ATTR_HIDDEN, ATTR_READ_ONLY, ATTR_VOLUME_ID, Clock,
+    DeleteOptions, Disk, Error, Fat32, Fat32Builder, FormatOptions, FsCheckReport, MountOptions,
+    SECTOR_SIZE, SliceDisk, Timestamp,
+};
+use bootloader::{BootInfo, entry_point};
+use core::fmt::Write;
+use core::panic::PanicInfo;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+entry_point!(main);
+
+fn main(boot_info: &'static BootInfo) -> ! {
+    use blog_os::allocator;
+    use blog_os::memory::{self, BootInfoFrameAllocator};
+    use x86_64::VirtAddr;
+
+    blog_os::init();
+    let phys_mem_offset = VirtAddr::new(boot_info.physical_memory_offset);
+    let mut mapper = unsafe { memory::init(phys_mem_offset) };
+    let mut frame_allocator = unsafe { BootInfoFrameAllocator::init(&boot_info.memory_map) };
+    allocator::init_heap(&mut mapper, &mut frame_allocator).expect("heap initialization failed");
+
+    test_main();
+    loop {}
+}
+
+const DISK_SECTORS: u32 = 70_000;
+
+/// Disque en mémoire qui ne conserve que les secteurs non nuls, pour tenir dans le tas.
+struct RamDisk {
+    sectors: BTreeMap<u32, Box<[u8; SECTOR_SIZE]>>,
+    sector_count: u32,
+}
+
+impl RamDisk {
+    fn new(sector_count: u32) -> Self {
+        RamDisk {
+            sectors: BTreeMap::new(),
+            sector_count,
+        }
+    }
+}
+
+impl Disk for RamDisk {
+    fn read_sector(&self, sector_lba: u32, buffer: &mut [u8]) -> Result<(), Error> {
+        if sector_lba >= self.sector_count {
+            return Err(Error::IoError);
+        }
+        match self.sectors.get(&sector_lba) {
+            Some(sector) => buffer[..SECTOR_SIZE].copy_from_slice(&sector[..]),
+            None => buffer[..SECTOR_SIZE].fill(0),
+        }
+        Ok(())
+    }
+
+    fn write_sector(&mut self, sector_lba: u32, buffer: &[u8]) -> Result<(), Error> {
+        if sector_lba >= self.sector_count {
+            return Err(Error::IoError);
+        }
+        if buffer[..SECTOR_SIZE].iter().all(|&b| b == 0) {
+            self.sectors.remove(&sector_lba);
+        } else {
+            let mut sector = Box::new([0u8; SECTOR_SIZE]);
+            sector.copy_from_slice(&buffer[..SECTOR_SIZE]);
+            self.sectors.insert(sector_lba, sector);
+        }
+        Ok(())
+    }
+
+    fn sector_count(&self) -> u32 {
+        self.sector_count
+    }
+}
+
+/// Disque qui refuse l'écriture d'un secteur donné.
+struct FailingDisk {
+    inner: RamDisk,
+    failing_lba: u32,
+}
+
+impl Disk for FailingDisk {
+    fn read_sector(&self, sector_lba: u32, buffer: &mut [u8]) -> Result<(), Error> {
+        self.inner.read_sector(sector_lba, buffer)
+    }
+
+    fn write_sector(&mut self, sector_lba: u32, buffer: &[u8]) -> Result<(), Error> {
+        if sector_lba == self.failing_lba {
+            return Err(Error::IoError);
+        }
+        self.inner.write_sector(sector_lba, buffer)
+    }
+
+    fn sector_count(&self) -> u32 {
+        self.inner.sector_count()
+    }
+}
+
+fn test_options() -> FormatOptions<'static> {
+    FormatOptions::default()
+}
+
+#[test_case]
+fn format_and_mount_empty_volume() {
+    let fs = Fat32::format_and_mount(RamDisk::new(DISK_SECTORS), test_options()).unwrap();
+    assert!(fs.read_directory(2).unwrap().is_empty());
+}
+
+#[test_case]
+fn format_rejects_invalid_geometry() {
+    for sectors_per_cluster in [0, 3, 8] {
+        let options = FormatOptions {
+            override_sectors_per_cluster: Some(sectors_per_cluster),
+            ..test_options()
+        };
+        let result = Fat32::format_and_mount(RamDisk::new(DISK_SECTORS), options);
+        assert_eq!(result.err(), Some(Error::InvalidFormatOptions));
+    }
+
+    let result = Fat32::format_and_mount(RamDisk::new(16), test_options());
+    assert_eq!(result.err(), Some(Error::InvalidFormatOptions));
+}
+
+#[test_case]
+fn fat1_flags_are_reported() {
+    let fs = Fat32::format_and_mount(RamDisk::new(DISK_SECTORS), test_options()).unwrap();
+    assert!(!fs.had_io_error());
+    assert!(fs.was_cleanly_unmounted());
+
+    let mut disk = RamDisk::new(DISK_SECTORS);
+    Fat32::format(&mut disk, &test_options()).unwrap();
+    let first_fat_sector = test_options().reserved_sectors as u32;
+    let mut buffer = [0u8; SECTOR_SIZE];
+    disk.read_sector(first_fat_sector, &mut buffer).unwrap();
+    buffer[4..8].copy_from_slice(&0x03FFFFFFu32.to_le_bytes());
+    disk.write_sector(first_fat_sector, &buffer).unwrap();
+
+    let fs = Fat32::mount(disk).unwrap();
+    assert!(fs.had_io_error());
+    assert!(!fs.was_cleanly_unmounted());
+}
+
+fn mount_empty() -> Fat32<RamDisk> {
+    Fat32::format_and_mount(RamDisk::new(DISK_SECTORS), test_options()).unwrap()
+}
+
+/// Démonte `fs` en vérifiant que les dernières écritures ont réussi.
+fn unmount<D: Disk>(fs: Fat32<D>) -> D {
+    let (disk, result) = fs.unmount();
+    result.unwrap();
+    disk
+}
+
+fn read_path(fs: &Fat32<RamDisk>, path: &str) -> Result<Vec<u8>, Error> {
+    let file = fs.find_entry(path, 2)?;
+    fs.read_file(&file)
+}
+
+#[test_case]
+fn write_and_read_back() {
+    let mut fs = mount_empty();
+    fs.write_file("/hello.txt", b"Hello, FAT32!").unwrap();
+    assert_eq!(read_path(&fs, "/HELLO.TXT").unwrap(), b"Hello, FAT32!");
+
+    let data: Vec<u8> = (0..3000u32).map(|i| i as u8).collect();
+    fs.write_file("/hello.txt", &data).unwrap();
+    assert_eq!(read_path(&fs, "hello.txt").unwrap(), data);
+
+    let entries = fs.read_directory(2).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].name, "HELLO.TXT");
+    assert_eq!(entries[0].size, 3000);
+}
+
+#[test_case]
+fn create_file_and_grow_directory() {
+    let mut fs = mount_empty();
+    for i in 0..20 {
+        fs.create_file(&format!("/file{}.dat", i)).unwrap();
+    }
+    assert_eq!(fs.create_file("/FILE3.DAT"), Err(Error::AlreadyExists));
+    assert_eq!(fs.read_directory(2).unwrap().len(), 20);
+    assert_eq!(read_path(&fs, "/file19.dat").unwrap(), b"");
+}
+
+#[test_case]
+fn streaming_write_fills_each_cluster() {
+    let mut fs = mount_empty();
+    let mut next = 0u8;
+    fs.write_file_streaming("/samples.bin", 1300, |buffer| {
+        for byte in buffer.iter_mut() {
+            *byte = next;
+            next = next.wrapping_add(1);
+        }
+        Ok(buffer.len())
+    })
+    .unwrap();
+    let content = read_path(&fs, "/samples.bin").unwrap();
+    assert_eq!(content.len(), 1300);
+    assert!(content.iter().enumerate().all(|(i, &b)| b == i as u8));
+
+    let result = fs.write_file_streaming("/samples.bin", 1300, |buffer| Ok(buffer.len() / 2));
+    assert_eq!(result, Err(Error::IncompleteWrite));
+    assert_eq!(read_path(&fs, "/samples.bin").unwrap(), content);
+}
+
+#[test_case]
+fn path_depth_is_limited() {
+    let mut disk = RamDisk::new(DISK_SECTORS);
+    Fat32::format(&mut disk, &test_options()).unwrap();
+    let options = MountOptions {
+        max_path_depth: 2,
+        ..MountOptions::default()
+    };
+    let mut fs = Fat32::mount_with_options(disk, options).unwrap();
+    fs.create_file("/a.txt").unwrap();
+    assert!(fs.find_entry("/a.txt", 2).is_ok());
+    assert_eq!(
+        fs.find_entry("/x/y/a.txt", 2).err(),
+        Some(Error::PathTooDeep)
+    );
+
+    let deep: String = (0..100).map(|_| "/..").collect();
+    let fs = mount_empty();
+    assert_eq!(fs.find_entry(&deep, 2).err(), Some(Error::PathTooDeep));
+}
+
+#[test_case]
+fn cluster_lba_conversions() {
+    let fs = mount_empty();
+    let first_data_sector = fs.cluster_to_lba(2).unwrap();
+    assert_eq!(fs.cluster_to_lba(1), Err(Error::InvalidFat32Structure));
+    assert_eq!(fs.lba_to_cluster(0), None);
+    assert_eq!(fs.lba_to_cluster(first_data_sector - 1), None);
+    assert_eq!(fs.lba_to_cluster(first_data_sector), Some(2));
+
+    let cluster = 1234;
+    let lba = fs.cluster_to_lba(cluster).unwrap();
+    assert_eq!(lba, first_data_sector + cluster - 2);
+    assert_eq!(fs.lba_to_cluster(lba), Some(cluster));
+    assert_eq!(fs.lba_to_cluster(DISK_SECTORS), None);
+}
+
+#[test_case]
+fn volume_label_survives_remount() {
+    let options = FormatOptions {
+        volume_label: Some("mydisk"),
+        ..test_options()
+    };
+    let mut fs = Fat32::format_and_mount(RamDisk::new(DISK_SECTORS), options).unwrap();
+    assert_eq!(fs.volume_label().unwrap().as_deref(), Some("MYDISK"));
+    assert!(fs.read_directory(2).unwrap().is_empty());
+
+    fs.set_volume_label("backup").unwrap();
+    let root_lba = fs.cluster_to_lba(2).unwrap();
+    let disk = unmount(fs);
+
+    let mut buffer = [0u8; SECTOR_SIZE];
+    disk.read_sector(0, &mut buffer).unwrap();
+    assert_eq!(&buffer[71..82], b"BACKUP     ");
+    disk.read_sector(root_lba, &mut buffer).unwrap();
+    assert_eq!(&buffer[0..11], b"BACKUP     ");
+    assert_eq!(buffer[11], 0x08);
+    assert_eq!(buffer[32], 0x00);
+
+    let fs = Fat32::mount(disk).unwrap();
+    assert_eq!(fs.volume_label().unwrap().as_deref(), Some("BACKUP"));
+}
+
+#[test_case]
+fn set_volume_label_creates_root_entry() {
+    let mut fs = mount_empty();
+    assert_eq!(fs.volume_label().unwrap(), None);
+    fs.create_file("/before.txt").unwrap();
+    fs.set_volume_label("LOGS").unwrap();
+    assert_eq!(fs.volume_label().unwrap().as_deref(), Some("LOGS"));
+    assert_eq!(fs.read_directory(2).unwrap().len(), 1);
+}
+
+#[test_case]
+fn leading_e5_byte_round_trips() {
+    let mut fs = mount_empty();
+    // 0xE5 correspond à « σ » en CP437
+    fs.write_file("/\u{3c3}bc.txt", b"kanji").unwrap();
+
+    let entries = fs.read_directory(2).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].name, "\u{3c3}BC.TXT");
+    assert_eq!(read_path(&fs, "/\u{3c3}bc.txt").unwrap(), b"kanji");
+
+    let root_lba = fs.cluster_to_lba(2).unwrap();
+    let disk = unmount(fs);
+    let mut buffer = [0u8; SECTOR_SIZE];
+    disk.read_sector(root_lba, &mut buffer).unwrap();
+    assert_eq!(&buffer[0..11], b"\x05BC     TXT");
+}
+
+#[test_case]
+fn layout_matches_format_geometry() {
+    let fs = mount_empty();
+    let layout = fs.layout();
+    assert_eq!(layout.first_fat_sector, 32);
+    assert_eq!(layout.fat_sectors, 2 * layout.fat_size);
+    assert_eq!(layout.first_data_sector, 32 + layout.fat_sectors);
+    assert_eq!(layout.sectors_per_cluster, 1);
+    assert_eq!(layout.root_cluster, 2);
+    assert_eq!(
+        layout.cluster_count,
+        DISK_SECTORS - layout.first_data_sector
+    );
+    assert!(layout.fat_size * 128 >= layout.cluster_count + 2);
+    assert_eq!(fs.cluster_to_lba(2), Ok(layout.first_data_sector));
+}
+
+#[test_case]
+fn delete_file_releases_clusters() {
+    let mut fs = mount_empty();
+    fs.write_file("/a.bin", &[0xAA; 1500]).unwrap();
+    let start = fs.find_entry("/a.bin", 2).unwrap().start_cluster;
+    fs.delete_file("/a.bin").unwrap();
+    assert_eq!(fs.find_entry("/a.bin", 2).err(), Some(Error::FileNotFound));
+    assert!(fs.read_directory(2).unwrap().is_empty());
+
+    fs.write_file("/b.bin", &[0xBB; 10]).unwrap();
+    assert_eq!(fs.find_entry("/b.bin", 2).unwrap().start_cluster, start);
+}
+
+#[test_case]
+fn secure_delete_scrubs_data_after_read_only_is_cleared() {
+    let mut fs = mount_empty();
+    fs.write_file("/secret.key", &[0x5A; 1500]).unwrap();
+    let start = fs.find_entry("/secret.key", 2).unwrap().start_cluster;
+    fs.set_attributes("/secret.key", ATTR_READ_ONLY).unwrap();
+
+    let scrub = DeleteOptions { scrub: true };
+    assert_eq!(
+        fs.delete_file_with_options("/secret.key", scrub),
+        Err(Error::ReadOnly)
+    );
+    assert!(fs.find_entry("/secret.key", 2).is_ok());
+
+    fs.set_attributes("/secret.key", ATTR_ARCHIVE).unwrap();
+    fs.delete_file_with_options("/secret.key", scrub).unwrap();
+
+    let first_lba = fs.cluster_to_lba(start).unwrap();
+    let disk = unmount(fs);
+    let mut buffer = [0u8; SECTOR_SIZE];
+    for lba in first_lba..first_lba + 3 {
+        disk.read_sector(lba, &mut buffer).unwrap();
+        assert!(buffer.iter().all(|&b| b == 0));
+    }
+}
+
+#[test_case]
+fn verify_file_detects_size_mismatch() {
+    let mut fs = mount_empty();
+    fs.write_file("/ok.bin", &[1; 1025]).unwrap();
+    fs.create_file("/empty.bin").unwrap();
+    fs.write_file("/bad.bin", &[2; 100]).unwrap();
+    assert_eq!(fs.verify_file("/ok.bin"), Ok(()));
+    assert_eq!(fs.verify_file("/empty.bin"), Ok(()));
+
+    let root_lba = fs.cluster_to_lba(2).unwrap();
+    let mut disk = unmount(fs);
+    let mut buffer = [0u8; SECTOR_SIZE];
+    disk.read_sector(root_lba, &mut buffer).unwrap();
+    // Troisième entrée : bad.bin, taille portée à 4 clusters
+    buffer[64 + 28..64 + 32].copy_from_slice(&2048u32.to_le_bytes());
+    disk.write_sector(root_lba, &buffer).unwrap();
+
+    let fs = Fat32::mount(disk).unwrap();
+    assert_eq!(
+        fs.verify_file("/bad.bin"),
+        Err(Error::InvalidFat32Structure)
+    );
+    assert_eq!(fs.verify_file("/ok.bin"), Ok(()));
+}
+
+#[test_case]
+fn mount_borrowed_disk() {
+    let mut disk = RamDisk::new(DISK_SECTORS);
+    Fat32::format(&mut disk, &test_options()).unwrap();
+    {
+        let mut fs = Fat32::mount(&mut disk).unwrap();
+        fs.write_file("/shared.txt", b"borrowed").unwrap();
+    }
+
+    let mut buffer = [0u8; SECTOR_SIZE];
+    disk.read_sector(0, &mut buffer).unwrap();
+    assert_eq!(&buffer[510..512], &[0x55, 0xAA]);
+
+    let mut fs = Fat32::mount(&disk).unwrap();
+    let file = fs.find_entry("/shared.txt", 2).unwrap();
+    assert_eq!(fs.read_file(&file).unwrap(), b"borrowed");
+    assert_eq!(fs.create_file("/other.txt"), Err(Error::ReadOnly));
+    // Rien n'a changé depuis le montage : le secteur FSInfo n'est pas réécrit
+    assert_eq!(fs.unmount().1, Ok(()));
+}
+
+#[test_case]
+fn dot_dot_with_cluster_zero_resolves_to_root() {
+    let mut fs = mount_empty();
+    fs.create_dir("/sub").unwrap();
+    fs.create_dir("/sub/deep").unwrap();
+    fs.write_file("/sub/deep/note.txt", b"deep").unwrap();
+    fs.create_file("/top.txt").unwrap();
+
+    let sub = fs.find_entry("/sub", 2).unwrap();
+    let parent = fs.find_entry("/sub/..", 2).unwrap();
+    assert!(parent.is_directory);
+    assert_eq!(parent.start_cluster, 2);
+    let names: Vec<String> = fs
+        .read_directory(parent.start_cluster)
+        .unwrap()
+        .into_iter()
+        .map(|e| e.name)
+        .collect();
+    assert_eq!(names, ["SUB", "TOP.TXT"]);
+
+    assert_eq!(
+        fs.find_entry("/sub/deep/..", 2).unwrap().start_cluster,
+        sub.start_cluster
+    );
+    assert_eq!(
+        read_path(&fs, "/sub/deep/../../sub/deep/note.txt").unwrap(),
+        b"deep"
+    );
+    fs.create_file("/sub/../second.txt").unwrap();
+    assert!(fs.find_entry("/second.txt", 2).is_ok());
+
+    let sub_lba = fs.cluster_to_lba(sub.start_cluster).unwrap();
+    let disk = unmount(fs);
+    let mut buffer = [0u8; SECTOR_SIZE];
+    disk.read_sector(sub_lba, &mut buffer).unwrap();
+    assert_eq!(&buffer[32..43], b"..         ");
+    assert_eq!(&buffer[32 + 20..32 + 22], &[0, 0]);
+    assert_eq!(&buffer[32 + 26..32 + 28], &[0, 0]);
+}
+
+#[test_case]
+fn raw_entry_returns_on_disk_bytes() {
+    let mut fs = mount_empty();
+    fs.write_file("/dir.txt", &[9; 700]).unwrap();
+    let file = fs.find_entry("/dir.txt", 2).unwrap();
+
+    let raw = fs.raw_entry("/dir.txt").unwrap();
+    assert_eq!(&raw[0..11], b"DIR     TXT");
+    assert_eq!(raw[11], ATTR_ARCHIVE);
+    assert_eq!(
+        u16::from_le_bytes([raw[26], raw[27]]) as u32,
+        file.start_cluster
+    );
+    assert_eq!(
+        u32::from_le_bytes([raw[28], raw[29], raw[30], raw[31]]),
+        700
+    );
+    assert_eq!(fs.raw_entry("/missing.txt"), Err(Error::FileNotFound));
+}
+
+#[test_case]
+fn read_file_rejects_oversized_files() {
+    let mut disk = RamDisk::new(DISK_SECTORS);
+    Fat32::format(&mut disk, &test_options()).unwrap();
+    let options = MountOptions {
+        max_read_size: 1024,
+        ..MountOptions::default()
+    };
+    let mut fs = Fat32::mount_with_options(disk, options).unwrap();
+    fs.write_file("/small.bin", &[1; 1024]).unwrap();
+    fs.write_file("/large.bin", &[2; 1025]).unwrap();
+    assert_eq!(read_path(&fs, "/small.bin").unwrap().len(), 1024);
+    assert_eq!(read_path(&fs, "/large.bin"), Err(Error::FileTooLarge));
+
+    let mut disk = RamDisk::new(DISK_SECTORS);
+    Fat32::format(&mut disk, &test_options()).unwrap();
+    let options = MountOptions {
+        max_read_size: u32::MAX,
+        ..MountOptions::default()
+    };
+    let mut fs = Fat32::mount_with_options(disk, options).unwrap();
+    fs.write_file("/huge.bin", &[3; 10]).unwrap();
+    let mut file = fs.find_entry("/huge.bin", 2).unwrap();
+    // Plus grand que le volume lui-même
+    file.size = 0x1000_0000;
+    assert_eq!(fs.read_file(&file), Err(Error::FileTooLarge));
+}
+
+#[test_case]
+fn free_count_is_synced_to_fs_info() {
+    let mut fs = mount_empty();
+    let cluster_count = fs.layout().cluster_count;
+    assert_eq!(fs.free_clusters(), Some(cluster_count - 1));
+
+    fs.write_file("/three.bin", &[4; 1536]).unwrap();
+    fs.write_file("/one.bin", &[5; 10]).unwrap();
+    fs.delete_file("/one.bin").unwrap();
+    assert_eq!(fs.free_clusters(), Some(cluster_count - 4));
+    fs.flush().unwrap();
+
+    let disk = unmount(fs);
+    let mut buffer = [0u8; SECTOR_SIZE];
+    disk.read_sector(1, &mut buffer).unwrap();
+    assert_eq!(
+        u32::from_le_bytes([buffer[488], buffer[489], buffer[490], buffer[491]]),
+        cluster_count - 4
+    );
+
+    let fs = Fat32::mount(disk).unwrap();
+    assert_eq!(fs.free_clusters(), Some(cluster_count - 4));
+}
+
+#[test_case]
+fn failed_unmount_returns_disk() {
+    let mut fs = mount_empty();
+    fs.write_file("/a.txt", b"kept").unwrap();
+    let disk = FailingDisk {
+        inner: unmount(fs),
+        failing_lba: 1,
+    };
+
+    let mut fs = Fat32::mount(disk).unwrap();
+    fs.write_file("/b.txt", b"new").unwrap();
+    let (mut disk, result) = fs.unmount();
+    assert_eq!(result, Err(Error::IoAt { lba: 1 }));
+    disk.failing_lba = u32::MAX;
+    let fs = Fat32::mount(disk).unwrap();
+    assert_eq!(
+        fs.read_file(&fs.find_entry("/b.txt", 2).unwrap()).unwrap(),
+        b"new"
+    );
+}
+
+#[test_case]
+fn bogus_fs_info_sector_is_skipped() {
+    let mut fs = mount_empty();
+    let cluster_count = fs.layout().cluster_count;
+    fs.write_file("/data.bin", &[6; 700]).unwrap();
+    let mut disk = unmount(fs);
+
+    for bogus in [0u16, 32, 0xFFFF] {
+        let mut boot = [0u8; SECTOR_SIZE];
+        disk.read_sector(0, &mut boot).unwrap();
+        boot[48..50].copy_from_slice(&bogus.to_le_bytes());
+        disk.write_sector(0, &boot).unwrap();
+
+        let mut fs = Fat32::mount(disk).unwrap();
+        assert_eq!(fs.free_clusters(), None);
+        assert_eq!(read_path(&fs, "/data.bin").unwrap(), [6; 700]);
+        assert_eq!(fs.recompute_free_count(), Ok(cluster_count - 3));
+        assert_eq!(fs.free_clusters(), Some(cluster_count - 3));
+        disk = unmount(fs);
+    }
+}
+
+#[test_case]
+fn walk_yields_full_paths_depth_first() {
+    let mut fs = mount_empty();
+    fs.create_dir("/a").unwrap();
+    fs.create_dir("/a/b").unwrap();
+    fs.write_file("/a/b/deep.txt", b"deep").unwrap();
+    fs.write_file("/top.txt", b"top").unwrap();
+
+    let paths: Vec<String> = fs
+        .walk("/")
+        .unwrap()
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect();
+    assert_eq!(paths, ["/A", "/A/B", "/A/B/DEEP.TXT", "/TOP.TXT"]);
+
+    let first: Vec<String> = fs
+        .walk_iter("/a")
+        .take(1)
+        .map(|entry| entry.unwrap().0)
+        .collect();
+    assert_eq!(first, ["/a/B"]);
+    assert!(matches!(
+        fs.walk_iter("/missing").next(),
+        Some(Err(Error::FileNotFound))
+    ));
+
+    let disk = unmount(fs);
+    let options = MountOptions {
+        max_path_depth: 2,
+        ..MountOptions::default()
+    };
+    let fs = Fat32::mount_with_options(disk, options).unwrap();
+    let entries: Vec<_> = fs.walk_iter("/").collect();
+    assert_eq!(entries.len(), 4);
+    assert!(matches!(entries[2], Err(Error::PathTooDeep)));
+}
+
+#[test_case]
+fn names_use_code_page_437() {
+    let mut fs = mount_empty();
+    fs.write_file("/caf\u{e9}.txt", b"cafe").unwrap();
+    fs.set_volume_label("\u{c7}a va").unwrap();
+    assert_eq!(fs.write_file("/\u{20ac}.txt", b""), Err(Error::InvalidPath));
+
+    assert_eq!(fs.read_directory(2).unwrap()[0].name, "CAF\u{c9}.TXT");
+    assert_eq!(
+        fs.raw_entry("/caf\u{e9}.txt").unwrap()[..11],
+        *b"CAF\x90    TXT"
+    );
+    assert_eq!(fs.create_file("/CAF\u{c9}.TXT"), Err(Error::AlreadyExists));
+    assert_eq!(fs.volume_label(), Ok(Some(String::from("\u{c7}A VA"))));
+    assert_eq!(fs.oem_name(), Ok(String::from("MSWIN4.1")));
+
+    // « â » n'a pas de majuscule en CP437 : DOS l'écrit « A »
+    fs.write_file("/t\u{e2}che.txt", b"todo").unwrap();
+    assert_eq!(fs.raw_entry("/TACHE.TXT").unwrap()[..11], *b"TACHE   TXT");
+
+    // Le même nom écrit tel quel par un autre système reste accessible sous son nom affiché
+    let lba = fs.cluster_to_lba(2).unwrap();
+    let mut disk = unmount(fs);
+    let mut sector = [0u8; SECTOR_SIZE];
+    disk.read_sector(lba, &mut sector).unwrap();
+    let entry = sector
+        .chunks_exact(32)
+        .position(|entry| entry[..11] == *b"TACHE   TXT")
+        .unwrap();
+    sector[entry * 32 + 1] = 0x83;
+    disk.write_sector(lba, &sector).unwrap();
+
+    let fs = Fat32::mount(disk).unwrap();
+    assert!(
+        fs.read_directory(2)
+            .unwrap()
+            .iter()
+            .any(|file| file.name == "T\u{e2}CHE.TXT")
+    );
+    for path in ["/T\u{e2}CHE.TXT", "/t\u{e2}che.txt"] {
+        assert_eq!(read_path(&fs, path).unwrap(), b"todo");
+    }
+}
+
+#[test_case]
+fn same_file_compares_first_clusters() {
+    let mut fs = mount_empty();
+    fs.create_dir("/dir").unwrap();
+    fs.write_file("/dir/data.bin", b"data").unwrap();
+    fs.create_file("/empty1").unwrap();
+    fs.create_file("/empty2").unwrap();
+
+    assert_eq!(fs.same_file("/dir/data.bin", "/DIR/DATA.BIN"), Ok(true));
+    assert_eq!(fs.same_file("/dir/.", "/dir"), Ok(true));
+    assert_eq!(fs.same_file("/dir/..", "/"), Ok(true));
+    assert_eq!(fs.same_file("/empty1", "/empty1"), Ok(true));
+    assert_eq!(fs.same_file("/empty1", "/empty2"), Ok(false));
+    assert_eq!(fs.same_file("/dir", "/dir/data.bin"), Ok(false));
+    assert_eq!(fs.same_file("/dir", "/missing"), Err(Error::FileNotFound));
+}
+
+#[test_case]
+fn copy_onto_itself_keeps_source() {
+    let mut fs = mount_empty();
+    fs.write_file("/src.txt", b"precious").unwrap();
+
+    assert_eq!(fs.copy_file("/src.txt", "/SRC.TXT"), Err(Error::SameFile));
+    assert_eq!(fs.rename("/src.txt", "/src.txt"), Ok(()));
+    assert_eq!(read_path(&fs, "/src.txt").unwrap(), b"precious");
+
+    fs.copy_file("/src.txt", "/copy.txt").unwrap();
+    assert_eq!(read_path(&fs, "/copy.txt").unwrap(), b"precious");
+    assert_eq!(fs.same_file("/src.txt", "/copy.txt"), Ok(false));
+    assert_eq!(
+        fs.rename("/src.txt", "/copy.txt"),
+        Err(Error::AlreadyExists)
+    );
+}
+
+#[test_case]
+fn rename_moves_directories() {
+    let mut fs = mount_empty();
+    fs.create_dir("/a").unwrap();
+    fs.create_dir("/b").unwrap();
+    fs.write_file("/a/file.txt", b"moved").unwrap();
+
+    assert_eq!(fs.rename("/a", "/a/inner"), Err(Error::InvalidPath));
+    fs.rename("/a", "/b/c").unwrap();
+    assert_eq!(fs.find_entry("/a", 2).err(), Some(Error::FileNotFound));
+    assert_eq!(read_path(&fs, "/b/c/file.txt").unwrap(), b"moved");
+    assert_eq!(fs.same_file("/b/c/..", "/b"), Ok(true));
+
+    fs.rename("/b/c/file.txt", "/top.txt").unwrap();
+    assert_eq!(read_path(&fs, "/top.txt").unwrap(), b"moved");
+}
+
+#[test_case]
+fn cluster_size_follows_volume_size() {
+    let fs = Fat32::format_and_mount(RamDisk::new(600_000), test_options()).unwrap();
+    assert_eq!(fs.layout().sectors_per_cluster, 8);
+
+    let options = FormatOptions {
+        override_sectors_per_cluster: Some(4),
+        ..test_options()
+    };
+    let fs = Fat32::format_and_mount(RamDisk::new(600_000), options).unwrap();
+    assert_eq!(fs.layout().sectors_per_cluster, 4);
+
+    let options = FormatOptions {
+        override_sectors_per_cluster: Some(16),
+        ..test_options()
+    };
+    let result = Fat32::format_and_mount(RamDisk::new(600_000), options);
+    assert_eq!(result.err(), Some(Error::InvalidFormatOptions));
+}
+
+#[test_case]
+fn end_of_chain_marker_is_configurable() {
+    let disk = unmount(mount_empty());
+    let options = MountOptions {
+        end_of_chain: 0x0FFFFFF8,
+        ..MountOptions::default()
+    };
+    let mut fs = Fat32::mount_with_options(disk, options).unwrap();
+    fs.write_file("/two.bin", &[1; 600]).unwrap();
+    let first_fat_sector = fs.layout().first_fat_sector;
+
+    let disk = unmount(fs);
+    let mut buffer = [0u8; SECTOR_SIZE];
+    disk.read_sector(first_fat_sector, &mut buffer).unwrap();
+    assert_eq!(buffer[12..16], 4u32.to_le_bytes());
+    assert_eq!(buffer[16..20], 0x0FFFFFF8u32.to_le_bytes());
+
+    let fs = Fat32::mount(disk).unwrap();
+    assert_eq!(read_path(&fs, "/two.bin").unwrap(), [1; 600]);
+    assert_eq!(fs.verify_file("/two.bin"), Ok(()));
+
+    let options = MountOptions {
+        end_of_chain: 0x0FFFFFF7,
+        ..MountOptions::default()
+    };
+    let result = Fat32::mount_with_options(unmount(fs), options);
+    assert_eq!(result.err(), Some(Error::InvalidMountOptions));
+}
+
+#[test_case]
+fn hidden_sectors_are_checked_against_partition_start() {
+    let mut disk = unmount(mount_empty());
+    let mut boot = [0u8; SECTOR_SIZE];
+    disk.read_sector(0, &mut boot).unwrap();
+    boot[28..32].copy_from_slice(&2048u32.to_le_bytes());
+    disk.write_sector(0, &boot).unwrap();
+
+    let fs = Fat32::mount(disk).unwrap();
+    assert_eq!(fs.hidden_sectors(), 2048);
+
+    let options = MountOptions {
+        partition_start: Some(2048),
+        ..MountOptions::default()
+    };
+    let fs = Fat32::mount_with_options(unmount(fs), options).unwrap();
+
+    let options = MountOptions {
+        partition_start: Some(63),
+        ..MountOptions::default()
+    };
+    let result = Fat32::mount_with_options(unmount(fs), options);
+    assert_eq!(result.err(), Some(Error::PartitionMismatch));
+}
+
+#[test_case]
+fn scan_fat_visits_every_cluster_once() {
+    let mut fs = mount_empty();
+    fs.write_file("/a.bin", &[1; 1000]).unwrap();
+    let cluster_count = fs.layout().cluster_count;
+
+    let mut visited = 0;
+    let mut next_cluster = 2;
+    let mut used = Vec::new();
+    fs.scan_fat(|cluster, entry| {
+        assert_eq!(cluster, next_cluster);
+        next_cluster += 1;
+        visited += 1;
+        if entry != 0 {
+            used.push((cluster, entry));
+        }
+    })
+    .unwrap();
+    assert_eq!(visited, cluster_count);
+    assert_eq!(used, [(2, 0x0FFFFFFF), (3, 4), (4, 0x0FFFFFFF)]);
+}
+
+#[test_case]
+fn volume_label_is_space_padded_without_dot() {
+    let mut fs = mount_empty();
+    for invalid in ["", " lead", "a*b", "my disk.1", "twelve chars"] {
+        assert_eq!(fs.set_volume_label(invalid), Err(Error::InvalidVolumeLabel));
+    }
+    fs.set_volume_label("backup 2024").unwrap();
+    fs.set_volume_label("my disk").unwrap();
+
+    let root_lba = fs.cluster_to_lba(2).unwrap();
+    let disk = unmount(fs);
+    let mut buffer = [0u8; SECTOR_SIZE];
+    disk.read_sector(root_lba, &mut buffer).unwrap();
+    assert_eq!(&buffer[0..11], b"MY DISK    ");
+    assert_eq!(buffer[11], 0x08);
+    disk.read_sector(0, &mut buffer).unwrap();
+    assert_eq!(&buffer[71..82], b"MY DISK    ");
+}
+
+#[test_case]
+fn short_name_only_directory_is_resolved() {
+    let fs = mount_empty();
+    let layout = fs.layout();
+    let data_lba = |cluster: u32| layout.first_data_sector + (cluster - 2);
+    let mut disk = unmount(fs);
+
+    // Entrées 8.3 écrites à la main, sans aucune entrée LFN
+    let entry = |name: &[u8; 11], attributes: u8, cluster: u16, size: u32| {
+        let mut raw = [0u8; 32];
+        raw[..11].copy_from_slice(name);
+        raw[11] = attributes;
+        raw[26..28].copy_from_slice(&cluster.to_le_bytes());
+        raw[28..32].copy_from_slice(&size.to_le_bytes());
+        raw
+    };
+    let mut sector = [0u8; SECTOR_SIZE];
+    sector[..32].copy_from_slice(&entry(b"DOS        ", 0x10, 3, 0));
+    disk.write_sector(data_lba(2), &sector).unwrap();
+
+    sector = [0u8; SECTOR_SIZE];
+    sector[..32].copy_from_slice(&entry(b".          ", 0x10, 3, 0));
+    sector[32..64].copy_from_slice(&entry(b"..         ", 0x10, 0, 0));
+    sector[64..96].copy_from_slice(&entry(b"README  TXT", 0x20, 4, 5));
+    disk.write_sector(data_lba(3), &sector).unwrap();
+
+    sector = [0u8; SECTOR_SIZE];
+    sector[..5].copy_from_slice(b"hello");
+    disk.write_sector(data_lba(4), &sector).unwrap();
+
+    disk.read_sector(layout.first_fat_sector, &mut sector)
+        .unwrap();
+    sector[12..16].copy_from_slice(&0x0FFFFFFFu32.to_le_bytes());
+    sector[16..20].copy_from_slice(&0x0FFFFFFFu32.to_le_bytes());
+    disk.write_sector(layout.first_fat_sector, &sector).unwrap();
+
+    let fs = Fat32::mount(disk).unwrap();
+    for path in ["/DOS/README.TXT", "/dos/readme.txt", "/Dos/ReadMe.Txt"] {
+        assert_eq!(read_path(&fs, path).unwrap(), b"hello");
+    }
+    assert_eq!(
+        fs.find_entry("/dos/readme", 2).err(),
+        Some(Error::FileNotFound)
+    );
+}
+
+#[test_case]
+fn failed_write_reports_sector_and_rolls_back() {
+    let fs = mount_empty();
+    let free_before = fs.free_clusters();
+    // Troisième cluster du fichier : les clusters 3, 4 et 5 sont alloués
+    let failing_lba = fs.cluster_to_lba(5).unwrap();
+    let disk = FailingDisk {
+        inner: unmount(fs),
+        failing_lba,
+    };
+
+    let mut fs = Fat32::mount(disk).unwrap();
+    assert_eq!(
+        fs.write_file("/big.bin", &[9; 1536]),
+        Err(Error::IoAt { lba: failing_lba })
+    );
+    assert_eq!(fs.free_clusters(), free_before);
+    assert_eq!(fs.recompute_free_count().ok(), free_before);
+    assert_eq!(
+        fs.find_entry("/big.bin", 2).err(),
+        Some(Error::FileNotFound)
+    );
+}
+
+#[test_case]
+fn truncate_and_preallocate_resize_chains() {
+    let mut fs = mount_empty();
+    assert_eq!(fs.clusters_needed(0), 0);
+    assert_eq!(fs.clusters_needed(1), 1);
+    assert_eq!(fs.clusters_needed(512), 1);
+    assert_eq!(fs.clusters_needed(0xFFFF_FFFF), 0x80_0000);
+
+    let free = fs.free_clusters().unwrap();
+    fs.write_file("/file.bin", &[7; 1500]).unwrap();
+    fs.truncate_file("/file.bin", 600).unwrap();
+    assert_eq!(fs.free_clusters(), Some(free - 2));
+    assert_eq!(fs.verify_file("/file.bin"), Ok(()));
+
+    fs.truncate_file("/file.bin", 1100).unwrap();
+    let content = read_path(&fs, "/file.bin").unwrap();
+    assert_eq!(content[..600], [7; 600]);
+    assert!(content[600..].iter().all(|&b| b == 0));
+    assert_eq!(content.len(), 1100);
+    assert_eq!(fs.verify_file("/file.bin"), Ok(()));
+
+    fs.truncate_file("/file.bin", 0).unwrap();
+    assert_eq!(fs.free_clusters(), Some(free));
+    assert_eq!(
+        fs.truncate_file("/file.bin", 0x1_0000_0000),
+        Err(Error::FileTooLarge)
+    );
+
+    fs.preallocate("/file.bin", 2000).unwrap();
+    assert_eq!(fs.free_clusters(), Some(free - 4));
+    assert_eq!(read_path(&fs, "/file.bin").unwrap(), b"");
+    fs.write_file("/file.bin", b"small").unwrap();
+    assert_eq!(fs.free_clusters(), Some(free - 1));
+}
+
+#[test_case]
+fn append_extends_files_within_size_limit() {
+    let mut fs = mount_empty();
+    fs.append_file("/log.txt", b"first line\n").unwrap();
+    let long = [b'x'; 700];
+    fs.append_file("/log.txt", &long).unwrap();
+    fs.append_file("/log.txt", b"end").unwrap();
+
+    let content = read_path(&fs, "/log.txt").unwrap();
+    assert_eq!(content.len(), 11 + 700 + 3);
+    assert_eq!(&content[..11], b"first line\n");
+    assert_eq!(content[11..711], long);
+    assert_eq!(&content[711..], b"end");
+    assert_eq!(fs.verify_file("/log.txt"), Ok(()));
+
+    fs.preallocate("/log.txt", 3000).unwrap();
+    let free = fs.free_clusters();
+    fs.append_file("/log.txt", &[b'y'; 1000]).unwrap();
+    assert_eq!(fs.free_clusters(), free);
+    assert_eq!(read_path(&fs, "/log.txt").unwrap()[714..], [b'y'; 1000]);
+
+    // Taille portée à 4 Gio - 2 octets directement dans l'entrée
+    let root_lba = fs.cluster_to_lba(2).unwrap();
+    let mut disk = unmount(fs);
+    let mut buffer = [0u8; SECTOR_SIZE];
+    disk.read_sector(root_lba, &mut buffer).unwrap();
+    buffer[28..32].copy_from_slice(&0xFFFF_FFFEu32.to_le_bytes());
+    disk.write_sector(root_lba, &buffer).unwrap();
+
+    let mut fs = Fat32::mount(disk).unwrap();
+    let free = fs.free_clusters();
+    assert_eq!(fs.append_file("/log.txt", b"ab"), Err(Error::FileTooLarge));
+    assert_eq!(fs.free_clusters(), free);
+    assert_eq!(fs.append_file("/log.txt", b"a"), Err(Error::DiskFull));
+}
+
+#[test_case]
+fn free_extents_report_fragmentation() {
+    let mut fs = mount_empty();
+    let last = fs.layout().cluster_count + 1;
+    assert_eq!(fs.free_extents().unwrap(), [(3, last - 2)]);
+
+    fs.write_file("/a.bin", &[1; 1024]).unwrap();
+    fs.write_file("/b.bin", &[2; 512]).unwrap();
+    fs.write_file("/c.bin", &[3; 512]).unwrap();
+    fs.delete_file("/a.bin").unwrap();
+    fs.delete_file("/c.bin").unwrap();
+    assert_eq!(fs.free_extents().unwrap(), [(3, 2), (6, last - 5)]);
+}
+
+#[test_case]
+fn truncated_image_is_rejected() {
+    let mut fs = mount_empty();
+    fs.write_file("/data.bin", &[5; 100]).unwrap();
+    let mut disk = unmount(fs);
+
+    disk.sector_count = DISK_SECTORS - 1;
+    assert_eq!(Fat32::mount(disk).err(), Some(Error::TruncatedVolume));
+}
+
+#[test_case]
+fn nth_cluster_seeks_into_files() {
+    let mut fs = mount_empty();
+    let mut data = Vec::new();
+    for i in 0..3u8 {
+        data.extend_from_slice(&[i + 1; 512]);
+    }
+    fs.write_file("/seek.bin", &data[..1300]).unwrap();
+    fs.create_file("/empty.bin").unwrap();
+
+    let third = fs.nth_cluster("/seek.bin", 2).unwrap();
+    let mut buffer = [0u8; 600];
+    assert_eq!(fs.read_cluster(third, &mut buffer), Ok(512));
+    assert_eq!(buffer[..276], [3; 276]);
+    let mut short = [0u8; 10];
+    let first = fs.nth_cluster("/seek.bin", 0).unwrap();
+    assert_eq!(fs.read_cluster(first, &mut short), Ok(10));
+    assert_eq!(short, [1; 10]);
+
+    assert_eq!(fs.nth_cluster("/seek.bin", 3), Err(Error::OutOfRange));
+    assert_eq!(fs.nth_cluster("/empty.bin", 0), Err(Error::OutOfRange));
+}
+
+#[test_case]
+fn creation_time_keeps_hundredths() {
+    let disk = unmount(mount_empty());
+    let mut fs = Fat32Builder::new().with_clock(&CLOCK).mount(disk).unwrap();
+    fs.create_file("/stamp").unwrap();
+
+    let raw = fs.raw_entry("/stamp").unwrap();
+    assert_eq!(raw[13], 125);
+    let file = fs.find_entry("/stamp", 2).unwrap();
+    let created = file.created.unwrap();
+    assert_eq!((created.second, created.millisecond), (43, 250));
+    let modified = file.modified.unwrap();
+    assert_eq!((modified.second, modified.millisecond), (42, 0));
+    assert_eq!((modified.year, modified.month, modified.day), (2024, 5, 17));
+    assert_eq!(modified.hour, 13);
+
+    let disk = unmount(fs);
+    let mut fs = Fat32::mount(disk).unwrap();
+    fs.create_file("/plain").unwrap();
+    assert!(fs.find_entry("/plain", 2).unwrap().created.is_none());
+}
+
+#[test_case]
+fn invalid_attribute_entries_are_skipped() {
+    let mut fs = mount_empty();
+    fs.create_dir("/sub").unwrap();
+    fs.write_file("/sub/good.txt", b"good").unwrap();
+    fs.write_file("/sub/bad1.txt", b"bad").unwrap();
+    fs.write_file("/bad2.txt", b"bad").unwrap();
+    assert_eq!(fs.check(), Ok(FsCheckReport::default()));
+
+    let sub_lba = fs
+        .cluster_to_lba(fs.find_entry("/sub", 2).unwrap().start_cluster)
+        .unwrap();
+    let root_lba = fs.cluster_to_lba(2).unwrap();
+    let mut disk = unmount(fs);
+    let mut buffer = [0u8; SECTOR_SIZE];
+    // bad1.txt : quatrième entrée de /sub, bits réservés
+    disk.read_sector(sub_lba, &mut buffer).unwrap();
+    buffer[3 * 32 + 11] = 0x60;
+    disk.write_sector(sub_lba, &buffer).unwrap();
+    // bad2.txt : deuxième entrée de la racine, VOLUME_ID et DIRECTORY
+    disk.read_sector(root_lba, &mut buffer).unwrap();
+    buffer[32 + 11] = 0x18;
+    disk.write_sector(root_lba, &buffer).unwrap();
+
+    let fs = Fat32::mount(disk).unwrap();
+    let sub = fs.find_entry("/sub", 2).unwrap();
+    let names: Vec<String> = fs
+        .read_directory(sub.start_cluster)
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.name)
+        .collect();
+    assert_eq!(names, [".", "..", "GOOD.TXT"]);
+    assert_eq!(fs.read_directory(2).unwrap().len(), 1);
+    assert_eq!(
+        fs.find_entry("/sub/bad1.txt", 2).err(),
+        Some(Error::FileNotFound)
+    );
+    assert_eq!(fs.check().unwrap().invalid_entries, 2);
+}
+
+#[test_case]
+fn mount_through_trait_object() {
+    let mut disk = unmount(mount_empty());
+    {
+        let object: &mut dyn Disk = &mut disk;
+        let mut fs: Fat32<&mut dyn Disk> = Fat32::mount(object).unwrap();
+        fs.write_file("/dyn.txt", b"dynamic").unwrap();
+        unmount(fs);
+    }
+
+    let object: &dyn Disk = &disk;
+    let mut fs = Fat32::mount(object).unwrap();
+    assert_eq!(fs.find_entry("/dyn.txt", 2).unwrap().size, 7);
+    assert_eq!(fs.write_file("/no.txt", b""), Err(Error::ReadOnly));
+}
+
+#[test_case]
+fn root_dir_handle_navigates_tree() {
+    let mut fs = mount_empty();
+    fs.create_dir("/docs").unwrap();
+    fs.write_file("/docs/note.txt", b"note").unwrap();
+    assert_eq!(fs.root_cluster(), fs.layout().root_cluster);
+
+    let root = fs.root_dir();
+    assert_eq!(root.cluster(), fs.root_cluster());
+    assert_eq!(root.entries().unwrap().len(), 1);
+    let docs = root.open_dir("DOCS").unwrap();
+    assert_eq!(docs.find("note.txt").unwrap().size, 4);
+    assert_eq!(docs.open_dir("..").unwrap().cluster(), fs.root_cluster());
+    assert_eq!(docs.open_dir("note.txt").err(), Some(Error::NotADirectory));
+    assert_eq!(fs.open_dir("/docs").unwrap().cluster(), docs.cluster());
+    assert_eq!(fs.open_dir("/").unwrap().cluster(), fs.root_cluster());
+}
+
+#[test_case]
+fn backup_boot_sector_must_be_reserved() {
+    let mut disk = unmount(mount_empty());
+    let mut boot = [0u8; SECTOR_SIZE];
+    disk.read_sector(0, &mut boot).unwrap();
+    let original = boot;
+
+    // Copie de secours annoncée en 6 avec seulement 4 secteurs réservés
+    boot[14..16].copy_from_slice(&4u16.to_le_bytes());
+    disk.write_sector(0, &boot).unwrap();
+    assert_eq!(Fat32::mount(disk).err(), Some(Error::InvalidFat32Structure));
+
+    let mut disk = unmount(mount_empty());
+    boot = original;
+    boot[50..52].copy_from_slice(&0xFFFFu16.to_le_bytes());
+    disk.write_sector(0, &boot).unwrap();
+    let mut fs = Fat32::mount(disk).unwrap();
+    fs.set_volume_label("nobackup").unwrap();
+    let disk = unmount(fs);
+    let mut buffer = [0u8; SECTOR_SIZE];
+    disk.read_sector(0xFFFF, &mut buffer).unwrap();
+    assert_eq!(buffer, [0; SECTOR_SIZE]);
+    disk.read_sector(6, &mut buffer).unwrap();
+    assert_eq!(&buffer[71..82], b"NO NAME    ");
+}
+
+#[test_case]
+fn entry_edits_preserve_neighbours() {
+    let disk = unmount(mount_empty());
+    let mut fs = Fat32Builder::new().with_clock(&CLOCK).mount(disk).unwrap();
+    for name in ["/a.txt", "/b.txt", "/c.txt"] {
+        fs.write_file(name, name.as_bytes()).unwrap();
+    }
+    let sector = |fs: &Fat32<RamDisk>| {
+        let mut buffer = [0u8; SECTOR_SIZE];
+        fs.read_cluster(2, &mut buffer).unwrap();
+        buffer
+    };
+    let before = sector(&fs);
+
+    fs.set_attributes("/b.txt", ATTR_READ_ONLY).unwrap();
+    fs.touch("/b.txt").unwrap();
+    fs.truncate_file("/b.txt", 2).unwrap();
+    let after = sector(&fs);
+    assert_ne!(before[32..64], after[32..64]);
+    assert_eq!(before[..32], after[..32]);
+    assert_eq!(before[64..], after[64..]);
+
+    fs.delete_file("/c.txt").unwrap();
+    assert_eq!(sector(&fs)[..64], after[..64]);
+    assert_eq!(sector(&fs)[64], 0xE5);
+}
+
+#[test_case]
+fn path_errors_name_the_failing_component() {
+    let mut fs = mount_empty();
+    fs.create_dir("/dir").unwrap();
+    fs.write_file("/dir/file.txt", b"data").unwrap();
+
+    assert_eq!(read_path(&fs, "/dir/missing"), Err(Error::FileNotFound));
+    assert_eq!(read_path(&fs, "/nowhere/file"), Err(Error::FileNotFound));
+    assert_eq!(
+        read_path(&fs, "/dir/file.txt/inner"),
+        Err(Error::NotADirectory)
+    );
+    assert_eq!(
+        fs.open_dir("/dir/file.txt").err(),
+        Some(Error::NotADirectory)
+    );
+    assert_eq!(read_path(&fs, "/dir"), Err(Error::IsADirectory));
+    assert_eq!(fs.delete_file("/dir"), Err(Error::IsADirectory));
+    for path in ["/", "//"] {
+        assert_eq!(read_path(&fs, path), Err(Error::IsADirectory));
+        assert_eq!(fs.delete_file(path), Err(Error::IsADirectory));
+        assert_eq!(fs.write_file(path, b""), Err(Error::IsADirectory));
+    }
+    assert_eq!(fs.write_file("/dir", b""), Err(Error::IsADirectory));
+    assert_eq!(fs.truncate_file("/dir", 0), Err(Error::IsADirectory));
+    assert_eq!(
+        fs.write_file("/dir/bad name.txt", b""),
+        Err(Error::InvalidPath)
+    );
+}
+
+#[test_case]
+fn dump_sector_returns_raw_bytes() {
+    let fs = mount_empty();
+    let boot = fs.dump_sector(0).unwrap();
+    assert_eq!(&boot[3..11], b"MSWIN4.1");
+    assert_eq!(boot[510..], [0x55, 0xAA]);
+
+    let fat = fs.dump_sector(fs.layout().first_fat_sector).unwrap();
+    assert_eq!(fat[8..12], 0x0FFFFFFFu32.to_le_bytes());
+    assert_eq!(
+        fs.dump_sector(DISK_SECTORS),
+        Err(Error::IoAt { lba: DISK_SECTORS })
+    );
+}
+
+#[test_case]
+fn fat_entries_keep_reserved_bits() {
+    let fs = mount_empty();
+    let layout = fs.layout();
+    let mut disk = unmount(fs);
+    for fat in 0..2 {
+        let lba = layout.first_fat_sector + fat * layout.fat_size;
+        let mut sector = [0u8; SECTOR_SIZE];
+        disk.read_sector(lba, &mut sector).unwrap();
+        sector[12..16].copy_from_slice(&0xA0000000u32.to_le_bytes());
+        disk.write_sector(lba, &sector).unwrap();
+    }
+
+    let mut fs = Fat32::mount(disk).unwrap();
+    fs.write_file("/a.bin", b"data").unwrap();
+    assert_eq!(fs.find_entry("/a.bin", 2).unwrap().start_cluster, 3);
+    for fat in 0..2 {
+        let sector = fs
+            .dump_sector(layout.first_fat_sector + fat * layout.fat_size)
+            .unwrap();
+        assert_eq!(sector[12..16], 0xAFFFFFFFu32.to_le_bytes());
+    }
+    assert_eq!(read_path(&fs, "/a.bin").unwrap(), b"data");
+}
+
+#[test_case]
+fn slice_disk_reads_borrowed_image() {
+    let disk = unmount(mount_empty());
+    let mut image = vec![0u8; 64 * SECTOR_SIZE + 100];
+    for (lba, sector) in image.chunks_exact_mut(SECTOR_SIZE).enumerate() {
+        disk.read_sector(lba as u32, sector).unwrap();
+    }
+
+    let mut slice = SliceDisk::new(&image);
+    assert_eq!(slice.sector_count(), 64);
+    let mut buffer = [0u8; SECTOR_SIZE];
+    slice.read_sector(6, &mut buffer).unwrap();
+    assert_eq!(buffer[..], image[6 * SECTOR_SIZE..7 * SECTOR_SIZE]);
+    assert_eq!(slice.read_sector(64, &mut buffer), Err(Error::IoError));
+    assert_eq!(slice.write_sector(0, &buffer), Err(Error::ReadOnly));
+
+    // Le secteur de démarrage est lu, mais l'image ne contient pas tout le volume
+    assert_eq!(Fat32::mount(slice).err(), Some(Error::TruncatedVolume));
+}
+
+#[test_case]
+fn clean_slice_disk_volume_flushes_and_unmounts() {
+    let fs = mount_empty();
+    let layout = fs.layout();
+    let disk = unmount(fs);
+
+    // Image réduite : zone réservée, un secteur par copie de la FAT et quatre clusters
+    let first_fat = layout.first_fat_sector;
+    let first_data = first_fat + 2;
+    let total = first_data + 4 * layout.sectors_per_cluster;
+    let mut image = vec![0u8; total as usize * SECTOR_SIZE];
+    let mut copy = |from: u32, to: u32| {
+        let start = to as usize * SECTOR_SIZE;
+        disk.read_sector(from, &mut image[start..start + SECTOR_SIZE])
+            .unwrap();
+    };
+    for lba in 0..first_fat {
+        copy(lba, lba);
+    }
+    copy(first_fat, first_fat);
+    copy(first_fat + layout.fat_size, first_fat + 1);
+    for sector in 0..layout.sectors_per_cluster {
+        copy(layout.first_data_sector + sector, first_data + sector);
+    }
+    image[32..36].copy_from_slice(&total.to_le_bytes());
+    image[36..40].copy_from_slice(&1u32.to_le_bytes());
+
+    let mut fs = Fat32::mount(SliceDisk::new(&image)).unwrap();
+    assert!(fs.read_directory(2).unwrap().is_empty());
+    assert_eq!(fs.flush(), Ok(()));
+    // Le nombre de clusters libres enregistré est invalide pour cette image : le recompter
+    // change les informations du volume, que le disque ne permet pas d'écrire
+    assert_eq!(fs.free_clusters(), None);
+    assert_eq!(fs.recompute_free_count(), Ok(3));
+    assert_eq!(fs.unmount().1, Ok(()));
+}
+
+#[test_case]
+fn parent_of_resolves_containing_directory() {
+    let mut fs = mount_empty();
+    fs.create_dir("/docs").unwrap();
+    fs.write_file("/docs/note.txt", b"x").unwrap();
+    let docs = fs.find_entry("/docs", 2).unwrap().start_cluster;
+
+    assert_eq!(
+        fs.parent_of("/docs/note.txt").unwrap(),
+        (docs, String::from("note.txt"))
+    );
+    assert_eq!(fs.parent_of("/docs/").unwrap(), (2, String::from("docs")));
+    assert_eq!(fs.parent_of("/"), Err(Error::InvalidPath));
+    assert_eq!(fs.parent_of("/missing/a"), Err(Error::FileNotFound));
+    assert_eq!(fs.parent_of("/docs/note.txt/a"), Err(Error::NotADirectory));
+}
+
+#[test_case]
+fn check_reports_cross_linked_entries() {
+    let mut fs = mount_empty();
+    fs.create_dir("/docs").unwrap();
+    fs.write_file("/docs/a.txt", b"first").unwrap();
+    fs.write_file("/docs/b.txt", b"second").unwrap();
+    assert!(fs.check().unwrap().cross_linked.is_empty());
+
+    let a = fs.find_entry("/docs/a.txt", 2).unwrap().start_cluster;
+    let docs = fs.find_entry("/docs", 2).unwrap().start_cluster;
+    let lba = fs.cluster_to_lba(docs).unwrap();
+    let mut disk = unmount(fs);
+    let mut sector = [0u8; SECTOR_SIZE];
+    disk.read_sector(lba, &mut sector).unwrap();
+    // Entrées `.`, `..`, A.TXT puis B.TXT
+    sector[96 + 26..96 + 28].copy_from_slice(&(a as u16).to_le_bytes());
+    disk.write_sector(lba, &sector).unwrap();
+
+    let fs = Fat32::mount(disk).unwrap();
+    assert_eq!(
+        fs.check().unwrap().cross_linked,
+        [(String::from("/DOCS/A.TXT"), String::from("/DOCS/B.TXT"))]
+    );
+}
+
+#[test_case]
+fn check_fails_beyond_path_depth() {
+    let mut fs = mount_empty();
+    fs.create_dir("/a").unwrap();
+    fs.create_dir("/a/b").unwrap();
+    fs.write_file("/a/b/deep.txt", b"deep").unwrap();
+    assert_eq!(fs.check(), Ok(FsCheckReport::default()));
+
+    let options = MountOptions {
+        max_path_depth: 2,
+        ..MountOptions::default()
+    };
+    let fs = Fat32::mount_with_options(unmount(fs), options).unwrap();
+    assert_eq!(fs.check(), Err(Error::PathTooDeep));
+}
+
+#[test_case]
+fn chain_length_counts_clusters() {
+    let mut fs = mount_empty();
+    fs.write_file("/a.bin", &[7; 1100]).unwrap();
+    let start = fs.find_entry("/a.bin", 2).unwrap().start_cluster;
+    assert_eq!(fs.chain_length(start), Ok(3));
+    assert_eq!(fs.chain_length(2), Ok(1));
+    assert_eq!(fs.chain_length(0), Err(Error::InvalidFat32Structure));
+
+    // Le deuxième maillon pointe sur un cluster libre
+    let layout = fs.layout();
+    let mut disk = unmount(fs);
+    let mut sector = [0u8; SECTOR_SIZE];
+    disk.read_sector(layout.first_fat_sector, &mut sector)
+        .unwrap();
+    let offset = (start as usize + 1) * 4;
+    sector[offset..offset + 4].copy_from_slice(&100u32.to_le_bytes());
+    disk.write_sector(layout.first_fat_sector, &sector).unwrap();
+
+    let fs = Fat32::mount(disk).unwrap();
+    assert_eq!(fs.chain_length(start), Err(Error::InvalidFat32Structure));
+    assert_eq!(fs.verify_file("/a.bin"), Err(Error::InvalidFat32Structure));
+}
+
+#[test_case]
+fn write_file_with_attrs_sets_attributes() {
+    let mut fs = mount_empty();
+    fs.write_file_with_attrs("/config.sys", b"cfg", ATTR_READ_ONLY | ATTR_HIDDEN)
+        .unwrap();
+    let info = fs.find_entry("/config.sys", 2).unwrap();
+    assert_eq!(info.attributes, ATTR_READ_ONLY | ATTR_HIDDEN);
+    assert_eq!(fs.read_file(&info).unwrap(), b"cfg");
+
+    fs.write_file("/config.sys", b"new").unwrap();
+    let info = fs.find_entry("/config.sys", 2).unwrap();
+    assert_eq!(info.attributes, ATTR_READ_ONLY | ATTR_HIDDEN);
+    fs.write_file_with_attrs("/config.sys", b"again", ATTR_ARCHIVE)
+        .unwrap();
+    assert_eq!(
+        fs.find_entry("/config.sys", 2).unwrap().attributes,
+        ATTR_ARCHIVE
+    );
+
+    for invalid in [ATTR_DIRECTORY, ATTR_VOLUME_ID, 0x40] {
+        assert_eq!(
+            fs.write_file_with_attrs("/bad.txt", b"", invalid),
+            Err(Error::InvalidAttributes)
+        );
+    }
+    assert_eq!(
+        fs.find_entry("/bad.txt", 2).err(),
+        Some(Error::FileNotFound)
+    );
+}
+
+#[test_case]
+fn corrupt_directory_cluster_is_rejected() {
+    let mut fs = mount_empty();
+    fs.create_dir("/bad").unwrap();
+    fs.create_dir("/far").unwrap();
+    fs.write_file("/bad/x.txt", b"x").unwrap();
+    let root_lba = fs.cluster_to_lba(2).unwrap();
+    let cluster_count = fs.layout().cluster_count;
+    let mut disk = unmount(fs);
+    let mut sector = [0u8; SECTOR_SIZE];
+    disk.read_sector(root_lba, &mut sector).unwrap();
+    sector[20..22].fill(0);
+    sector[26..28].fill(0);
+    sector[32 + 20..32 + 22].copy_from_slice(&((cluster_count + 10) >> 16).to_le_bytes()[..2]);
+    sector[32 + 26..32 + 28].copy_from_slice(&((cluster_count + 10) as u16).to_le_bytes());
+    disk.write_sector(root_lba, &sector).unwrap();
+
+    let fs = Fat32::mount(disk).unwrap();
+    for path in ["/bad/x.txt", "/far/x.txt"] {
+        assert!(matches!(
+            fs.find_entry(path, 2),
+            Err(Error::InvalidFat32Structure)
+        ));
+    }
+    assert_eq!(
+        fs.open_dir("/bad").err(),
+        Some(Error::InvalidFat32Structure)
+    );
+    assert_eq!(
+        fs.root_dir().open_dir("far").err(),
+        Some(Error::InvalidFat32Structure)
+    );
+}
+
+#[test_case]
+fn random_directory_entries_do_not_panic() {
+    let mut fs = mount_empty();
+    fs.create_dir("/dir").unwrap();
+    let lba = fs
+        .cluster_to_lba(fs.find_entry("/dir", 2).unwrap().start_cluster)
+        .unwrap();
+    let mut disk = unmount(fs);
+
+    let mut seed = 0x2545F491u32;
+    for _ in 0..32 {
+        let mut sector = [0u8; SECTOR_SIZE];
+        disk.read_sector(lba, &mut sector).unwrap();
+        for byte in &mut sector[64..] {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            *byte = seed as u8;
+        }
+        disk.write_sector(lba, &sector).unwrap();
+
+        // Les tailles aléatoires ne doivent pas épuiser le tas du noyau
+        let fs = Fat32Builder::new()
+            .max_read_size(4096)
+            .mount(&mut disk)
+            .unwrap();
+        let _ = fs.check();
+        for (path, info) in fs.walk_iter("/").flatten() {
+            let _ = fs.find_entry(&path, 2);
+            let _ = fs.open_dir(&path);
+            let _ = fs.read_file(&info);
+            let _ = fs.verify_file(&path);
+        }
+    }
+}
+
+#[test_case]
+fn subtree_size_counts_allocated_clusters() {
+    let mut fs = mount_empty();
+    fs.create_dir("/docs").unwrap();
+    fs.create_dir("/docs/old").unwrap();
+    fs.write_file("/docs/a.txt", &[1; 600]).unwrap();
+    fs.write_file("/docs/old/b.txt", b"b").unwrap();
+    fs.create_file("/docs/empty.txt").unwrap();
+    fs.write_file("/top.txt", b"t").unwrap();
+
+    // Deux répertoires, deux clusters pour A.TXT et un pour B.TXT
+    assert_eq!(fs.subtree_size("/docs"), Ok(5 * 512));
+    assert_eq!(fs.subtree_size("/docs/a.txt"), Ok(2 * 512));
+    assert_eq!(fs.subtree_size("/docs/empty.txt"), Ok(0));
+    assert_eq!(fs.subtree_size("/"), Ok(7 * 512));
+    assert_eq!(fs.subtree_size("/missing"), Err(Error::FileNotFound));
+
+    let disk = unmount(fs);
+    let fs = Fat32Builder::new().max_path_depth(1).mount(disk).unwrap();
+    assert_eq!(fs.subtree_size("/docs"), Err(Error::PathTooDeep));
+}
+
+#[test_case]
+fn deferred_fat_mirror_is_synced_on_flush() {
+    let disk = unmount(mount_empty());
+    let mut fs = Fat32Builder::new()
+        .deferred_fat_mirror(true)
+        .mount(disk)
+        .unwrap();
+    let layout = fs.layout();
+    let mirror = layout.first_fat_sector + layout.fat_size;
+    let before = fs.dump_sector(mirror).unwrap();
+
+    fs.write_file("/a.bin", &[3; 2000]).unwrap();
+    assert_eq!(fs.dump_sector(mirror).unwrap(), before);
+    assert_ne!(fs.dump_sector(layout.first_fat_sector).unwrap(), before);
+
+    fs.flush().unwrap();
+    assert_eq!(
+        fs.dump_sector(mirror).unwrap(),
+        fs.dump_sector(layout.first_fat_sector).unwrap()
+    );
+
+    fs.delete_file("/a.bin").unwrap();
+    let disk = unmount(fs);
+    let fs = Fat32::mount(disk).unwrap();
+    assert_eq!(fs.dump_sector(mirror).unwrap(), before);
+}
+
+#[test_case]
+fn nt_lowercase_flags_apply_to_base_and_extension() {
+    let mut fs = mount_empty();
+    for name in ["/a.txt", "/b.txt", "/c.txt", "/d.txt"] {
+        fs.write_file(name, b"").unwrap();
+    }
+    let root_lba = fs.cluster_to_lba(2).unwrap();
+    let mut disk = unmount(fs);
+    let mut sector = [0u8; SECTOR_SIZE];
+    disk.read_sector(root_lba, &mut sector).unwrap();
+    for (slot, (name, flags)) in [
+        (b"CONFIG  TXT", 0x00),
+        (b"CONFIG  SYS", 0x08),
+        (b"README  MD ", 0x10),
+        (b"SETUP   INI", 0x18),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        sector[slot * 32..slot * 32 + 11].copy_from_slice(name);
+        sector[slot * 32 + 12] = flags;
+    }
+    disk.write_sector(root_lba, &sector).unwrap();
+
+    let fs = Fat32::mount(disk).unwrap();
+    let names: Vec<String> = fs
+        .read_directory(2)
+        .unwrap()
+        .into_iter()
+        .map(|info| info.name)
+        .collect();
+    assert_eq!(
+        names,
+        ["CONFIG.TXT", "config.SYS", "README.md", "setup.ini"]
+    );
+    assert!(fs.find_entry("/CONFIG.SYS", 2).is_ok());
+    assert!(fs.find_entry("/readme.md", 2).is_ok());
+}
+
+#[test_case]
+fn read_file_into_fills_caller_buffer() {
+    let mut fs = mount_empty();
+    let data: Vec<u8> = (0..1300).map(|i| i as u8).collect();
+    fs.write_file("/a.bin", &data).unwrap();
+
+    let mut buffer = [0u8; 2048];
+    assert_eq!(fs.read_file_into("/a.bin", &mut buffer, true), Ok(1300));
+    assert_eq!(buffer[..1300], data[..]);
+
+    let mut small = [0u8; 700];
+    assert_eq!(
+        fs.read_file_into("/a.bin", &mut small, true),
+        Err(Error::BufferTooSmall)
+    );
+    assert_eq!(fs.read_file_into("/a.bin", &mut small, false), Ok(700));
+    assert_eq!(small[..], data[..700]);
+
+    fs.create_file("/empty.txt").unwrap();
+    assert_eq!(fs.read_file_into("/empty.txt", &mut small, true), Ok(0));
+    assert_eq!(
+        fs.read_file_into("/", &mut small, false),
+        Err(Error::IsADirectory)
+    );
+}
+
+#[test_case]
+fn deleted_entries_report_recoverable_files() {
+    let mut fs = mount_empty();
+    fs.create_dir("/docs").unwrap();
+    fs.write_file("/docs/a.txt", b"a").unwrap();
+    fs.write_file("/docs/b.bin", &[2; 700]).unwrap();
+    fs.create_file("/docs/empty").unwrap();
+    let a = fs.find_entry("/docs/a.txt", 2).unwrap().start_cluster;
+    let b = fs.find_entry("/docs/b.bin", 2).unwrap().start_cluster;
+    for path in ["/docs/a.txt", "/docs/b.bin", "/docs/empty"] {
+        fs.delete_file(path).unwrap();
+    }
+    assert!(fs.deleted_entries("/").unwrap().is_empty());
+
+    // Le cluster de A.TXT est réutilisé par une autre chaîne
+    let layout = fs.layout();
+    let mut disk = unmount(fs);
+    let mut sector = [0u8; SECTOR_SIZE];
+    disk.read_sector(layout.first_fat_sector, &mut sector)
+        .unwrap();
+    sector[a as usize * 4..a as usize * 4 + 4].copy_from_slice(&0x0FFFFFFFu32.to_le_bytes());
+    disk.write_sector(layout.first_fat_sector, &sector).unwrap();
+
+    let fs = Fat32::mount(disk).unwrap();
+    let deleted = fs.deleted_entries("/docs").unwrap();
+    let summary: Vec<_> = deleted
+        .iter()
+        .map(|e| {
+            (
+                e.slot,
+                e.name.as_str(),
+                e.start_cluster,
+                e.size,
+                e.recoverable,
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (2, "?.TXT", a, 1, false),
+            (3, "?.BIN", b, 700, true),
+            (4, "?MPTY", 0, 0, true),
+        ]
+    );
+    assert_eq!(
+        fs.deleted_entries("/docs/missing").err(),
+        Some(Error::FileNotFound)
+    );
+}
+
+#[test_case]
+fn undelete_relinks_contiguous_clusters() {
+    let mut fs = mount_empty();
+    fs.create_dir("/sub").unwrap();
+    let data: Vec<u8> = (0..1200).map(|i| i as u8).collect();
+    fs.write_file("/report.txt", &data).unwrap();
+    fs.write_file("/other.txt", b"other").unwrap();
+    let free = fs.free_clusters();
+    fs.delete_file("/report.txt").unwrap();
+    fs.delete_file("/other.txt").unwrap();
+    let slot = fs.deleted_entries("/").unwrap()[0].slot;
+
+    assert_eq!(fs.undelete("/", slot, ' '), Err(Error::InvalidPath));
+    assert_eq!(fs.undelete("/", slot + 5, 'R'), Err(Error::FileNotFound));
+    fs.undelete("/", slot, 'r').unwrap();
+    assert_eq!(read_path(&fs, "/report.txt").unwrap(), data);
+    assert_eq!(fs.verify_file("/report.txt"), Ok(()));
+    assert_eq!(fs.undelete("/", slot, 'R'), Err(Error::FileNotFound));
+
+    // Le cluster de OTHER.TXT est réattribué avant la restauration
+    fs.write_file("/sub/new.txt", b"new").unwrap();
+    let other = fs.deleted_entries("/").unwrap()[0].clone();
+    assert!(!other.recoverable);
+    assert_eq!(
+        fs.undelete("/", other.slot, 'O'),
+        Err(Error::ClustersReused)
+    );
+    assert_eq!(
+        fs.find_entry("/other.txt", 2).err(),
+        Some(Error::FileNotFound)
+    );
+    assert_eq!(fs.free_clusters(), free);
+}
+
+#[test_case]
+fn usage_ratio_is_unknown_without_clusters() {
+    let layout = mount_empty().layout();
+    let mut disk = RamDisk::new(DISK_SECTORS);
+    Fat32::format(&mut disk, &test_options()).unwrap();
+    // Une zone de données plus petite qu'un cluster
+    let mut boot = [0u8; SECTOR_SIZE];
+    disk.read_sector(0, &mut boot).unwrap();
+    boot[13] = 2;
+    boot[32..36].copy_from_slice(&(layout.first_data_sector + 1).to_le_bytes());
+    disk.write_sector(0, &boot).unwrap();
+    let mut fs_info = [0u8; SECTOR_SIZE];
+    disk.read_sector(1, &mut fs_info).unwrap();
+    fs_info[488..492].fill(0);
+    disk.write_sector(1, &fs_info).unwrap();
+
+    let fs = Fat32::mount(disk).unwrap();
+    assert_eq!(fs.layout().cluster_count, 0);
+    assert_eq!(fs.free_clusters(), Some(0));
+    assert_eq!(fs.usage_ratio(), None);
+}
+
+#[test_case]
+fn failed_undelete_frees_relinked_clusters() {
+    let mut fs = mount_empty();
+    fs.write_file("/report.txt", &[3; 1200]).unwrap();
+    fs.delete_file("/report.txt").unwrap();
+    let slot = fs.deleted_entries("/").unwrap()[0].slot;
+    let failing_lba = fs.cluster_to_lba(2).unwrap();
+    let disk = FailingDisk {
+        inner: unmount(fs),
+        failing_lba,
+    };
+
+    let mut fs = Fat32::mount(disk).unwrap();
+    let free = fs.free_clusters();
+    assert_eq!(
+        fs.undelete("/", slot, 'R'),
+        Err(Error::IoAt { lba: failing_lba })
+    );
+    assert_eq!(fs.free_clusters(), free);
+    assert_eq!(fs.recompute_free_count().ok(), free);
+}
+
+#[test_case]
+fn usage_ratio_follows_allocations() {
+    let mut fs = mount_empty();
+    let cluster_count = fs.layout().cluster_count;
+    assert_eq!(fs.usage_ratio(), Some(1.0 / cluster_count as f32));
+    assert_eq!(fs.is_full(), Some(false));
+
+    fs.write_file("/a.bin", &[0; 512 * 99]).unwrap();
+    assert_eq!(fs.usage_ratio(), Some(100.0 / cluster_count as f32));
+
+    let layout = fs.layout();
+    let mut disk = unmount(fs);
+    let mut fs_info = [0u8; SECTOR_SIZE];
+    disk.read_sector(1, &mut fs_info).unwrap();
+    fs_info[488..492].fill(0);
+    disk.write_sector(1, &fs_info).unwrap();
+    let fs = Fat32Builder::new().read_only(true).mount(disk).unwrap();
+    assert_eq!(fs.usage_ratio(), Some(1.0));
+    assert_eq!(fs.is_full(), Some(true));
+
+    let mut disk = unmount(fs);
+    // Sans FSInfo valide, le taux n'est connu qu'après un recomptage
+    disk.write_sector(1, &[0; SECTOR_SIZE]).unwrap();
+    let mut fs = Fat32::mount(disk).unwrap();
+    assert_eq!(fs.usage_ratio(), None);
+    assert_eq!(fs.is_full(), None);
+    fs.recompute_free_count().unwrap();
+    assert_eq!(fs.usage_ratio(), Some(100.0 / layout.cluster_count as f32));
+}
+
+#[test_case]
+fn write_at_patches_and_extends_files() {
+    let mut fs = mount_empty();
+    let mut expected: Vec<u8> = (0..700).map(|i| i as u8).collect();
+    fs.write_file("/a.bin", &expected).unwrap();
+
+    // Au milieu du dernier secteur, partiellement rempli
+    fs.write_at("/a.bin", 600, b"xyz").unwrap();
+    expected[600..603].copy_from_slice(b"xyz");
+    // À cheval sur deux clusters
+    fs.write_at("/a.bin", 510, b"abcd").unwrap();
+    expected[510..514].copy_from_slice(b"abcd");
+    assert_eq!(read_path(&fs, "/a.bin").unwrap(), expected);
+
+    // Au-delà de la fin : le trou est rempli de zéros
+    fs.write_at("/a.bin", 1100, b"end").unwrap();
+    expected.resize(1100, 0);
+    expected.extend_from_slice(b"end");
+    assert_eq!(read_path(&fs, "/a.bin").unwrap(), expected);
+    assert_eq!(fs.verify_file("/a.bin"), Ok(()));
+
+    fs.create_file("/empty.txt").unwrap();
+    fs.write_at("/empty.txt", 2, b"hi").unwrap();
+    assert_eq!(read_path(&fs, "/empty.txt").unwrap(), b"\0\0hi");
+    assert_eq!(
+        fs.write_at("/missing.txt", 0, b"x"),
+        Err(Error::FileNotFound)
+    );
+    assert_eq!(
+        fs.write_at("/a.bin", u32::MAX, b"xy"),
+        Err(Error::FileTooLarge)
+    );
+}
+
+#[test_case]
+fn write_at_releases_new_clusters_when_entry_update_fails() {
+    let mut fs = mount_empty();
+    fs.write_file("/a.bin", b"data").unwrap();
+    let failing_lba = fs.cluster_to_lba(2).unwrap();
+    let disk = FailingDisk {
+        inner: unmount(fs),
+        failing_lba,
+    };
+
+    let mut fs = Fat32::mount(disk).unwrap();
+    let free_before = fs.free_clusters();
+    assert_eq!(
+        fs.write_at("/a.bin", 4, &[7; 1500]),
+        Err(Error::IoAt { lba: failing_lba })
+    );
+    assert_eq!(fs.free_clusters(), free_before);
+    assert_eq!(fs.chain_length(fs.nth_cluster("/a.bin", 0).unwrap()), Ok(1));
+}
+
+#[test_case]
+fn file_handle_writes_sequentially() {
+    let disk = unmount(mount_empty());
+    let mut fs = Fat32Builder::new().with_clock(&CLOCK).mount(disk).unwrap();
+    fs.write_file("/log.txt", b"0123456789").unwrap();
+    let created = fs.find_entry("/log.txt", 2).unwrap().modified;
+
+    let mut file = fs.open_mut("/log.txt").unwrap();
+    assert_eq!(file.write(b"ab"), Ok(2));
+    file.seek(10).unwrap();
+    let line = [b'x'; 600];
+    assert_eq!(file.write(&line), Ok(600));
+    assert_eq!(file.position(), 610);
+    assert_eq!(file.len(), 610);
+    assert_eq!(file.seek(611), Err(Error::OutOfRange));
+    file.flush().unwrap();
+    drop(file);
+
+    let mut expected = b"ab23456789".to_vec();
+    expected.extend_from_slice(&line);
+    assert_eq!(read_path(&fs, "/log.txt").unwrap(), expected);
+    assert_ne!(fs.find_entry("/log.txt", 2).unwrap().modified, created);
+    assert!(matches!(fs.open_mut("/"), Err(Error::IsADirectory)));
+    fs.create_dir("/dir").unwrap();
+    assert!(matches!(fs.open_mut("/dir"), Err(Error::IsADirectory)));
+}
+
+#[test_case]
+fn formatted_writes_are_buffered_per_sector() {
+    let mut fs = mount_empty();
+    fs.write_file("/log.txt", b"start\n").unwrap();
+    let mut expected = String::from("start\n");
+    {
+        let mut file = fs.open_mut("/log.txt").unwrap();
+        file.seek(6).unwrap();
+        for t in 0..100 {
+            writeln!(file, "temp={}", t).unwrap();
+            writeln!(expected, "temp={}", t).unwrap();
+        }
+        assert_eq!(file.len(), expected.len() as u32);
+        file.flush().unwrap();
+        write!(file, "tail").unwrap();
+        expected.push_str("tail");
+    }
+    assert_eq!(read_path(&fs, "/log.txt").unwrap(), expected.as_bytes());
+
+    let mut file = fs.open_mut("/log.txt").unwrap();
+    file.seek(expected.len() as u32).unwrap();
+    write!(file, "{}", "z".repeat(SECTOR_SIZE)).unwrap();
+    // Seuls les secteurs complets sont déjà écrits, le reste l'est à l'abandon du fichier
+    assert_eq!(file.len(), (expected.len() + SECTOR_SIZE) as u32);
+    drop(file);
+    expected.push_str(&"z".repeat(SECTOR_SIZE));
+    assert_eq!(read_path(&fs, "/log.txt").unwrap(), expected.as_bytes());
+}
+
+#[test_case]
+fn append_handle_starts_at_end_of_file() {
+    let mut fs = mount_empty();
+    fs.write_file("/log.txt", b"start\n").unwrap();
+    let mut file = fs.open_append("/log.txt").unwrap();
+    assert_eq!(file.position(), 6);
+    writeln!(file, "temp={}", 21).unwrap();
+    drop(file);
+    assert_eq!(read_path(&fs, "/log.txt").unwrap(), b"start\ntemp=21\n");
+
+    let mut file = fs.open_mut("/log.txt").unwrap();
+    write!(file, "S").unwrap();
+    drop(file);
+    assert_eq!(read_path(&fs, "/log.txt").unwrap(), b"Start\ntemp=21\n");
+    assert!(matches!(fs.open_append("/"), Err(Error::IsADirectory)));
+}
+
+#[test_case]
+fn file_handle_writes_entry_on_flush_only() {
+    let mut fs = mount_empty();
+    fs.write_file("/log.txt", b"").unwrap();
+    // L'entrée du fichier est dans le premier secteur de la racine
+    let failing_lba = fs.cluster_to_lba(2).unwrap();
+    let disk = FailingDisk {
+        inner: unmount(fs),
+        failing_lba,
+    };
+
+    let mut fs = Fat32::mount(disk).unwrap();
+    let free_before = fs.free_clusters();
+    let mut file = fs.open_mut("/log.txt").unwrap();
+    // Le premier cluster est inscrit dans l'entrée dès son allocation
+    assert_eq!(file.write(b"x"), Err(Error::IoAt { lba: failing_lba }));
+    drop(file);
+    assert_eq!(fs.free_clusters(), free_before);
+
+    let mut disk = unmount(fs);
+    disk.failing_lba = u32::MAX;
+    let mut fs = Fat32::mount(disk).unwrap();
+    fs.write_file("/log.txt", b"start").unwrap();
+    let mut disk = unmount(fs);
+    disk.failing_lba = failing_lba;
+
+    let mut fs = Fat32::mount(disk).unwrap();
+    let mut file = fs.open_mut("/log.txt").unwrap();
+    file.seek(5).unwrap();
+    let data = [b'y'; 5000];
+    assert_eq!(file.write(&data), Ok(5000));
+    assert_eq!(file.len(), 5005);
+    assert_eq!(file.flush(), Err(Error::IoAt { lba: failing_lba }));
+    drop(file);
+    assert_eq!(fs.find_entry("/log.txt", 2).unwrap().size, 5);
+}
+
+#[test_case]
+fn failed_directory_growth_releases_new_cluster() {
+    let mut fs = mount_empty();
+    fs.create_dir("/sub").unwrap();
+    let sub = fs.nth_cluster("/sub", 0).unwrap();
+    // « . » et « .. » occupent déjà deux entrées du premier cluster
+    let entries = fs.layout().sectors_per_cluster * 16;
+    for i in 0..entries - 2 {
+        fs.create_file(&format!("/sub/f{}", i)).unwrap();
+    }
+    let failing_lba = fs.cluster_to_lba(sub + 1).unwrap();
+    let disk = FailingDisk {
+        inner: unmount(fs),
+        failing_lba,
+    };
+
+    let mut fs = Fat32::mount(disk).unwrap();
+    let free = fs.free_clusters();
+    assert_eq!(
+        fs.create_file("/sub/full"),
+        Err(Error::IoAt { lba: failing_lba })
+    );
+    assert_eq!(fs.free_clusters(), free);
+    assert_eq!(fs.chain_length(sub), Ok(1));
+    assert_eq!(fs.read_directory(sub).unwrap().len(), entries as usize);
+}
+
+#[test_case]
+fn failed_overwrite_releases_new_chain() {
+    let mut fs = mount_empty();
+    fs.write_file("/a.bin", b"old").unwrap();
+    let failing_lba = fs.cluster_to_lba(2).unwrap();
+    let disk = FailingDisk {
+        inner: unmount(fs),
+        failing_lba,
+    };
+
+    let mut fs = Fat32::mount(disk).unwrap();
+    let free = fs.free_clusters();
+    assert_eq!(
+        fs.write_file("/a.bin", &[1; 3000]),
+        Err(Error::IoAt { lba: failing_lba })
+    );
+    assert_eq!(fs.free_clusters(), free);
+    assert_eq!(fs.recompute_free_count().ok(), free);
+    let file = fs.find_entry("/a.bin", 2).unwrap();
+    assert_eq!(fs.read_file(&file).unwrap(), b"old");
+}
+
+#[test_case]
+fn failed_resize_releases_new_clusters() {
+    let mut fs = mount_empty();
+    fs.create_file("/e.bin").unwrap();
+    fs.write_file("/d.bin", b"data").unwrap();
+    let failing_lba = fs.cluster_to_lba(2).unwrap();
+    let disk = FailingDisk {
+        inner: unmount(fs),
+        failing_lba,
+    };
+
+    let mut fs = Fat32::mount(disk).unwrap();
+    let free = fs.free_clusters();
+    let failed = Err(Error::IoAt { lba: failing_lba });
+    assert_eq!(fs.truncate_file("/e.bin", 9000), failed);
+    assert_eq!(fs.truncate_file("/d.bin", 9000), failed);
+    assert_eq!(fs.preallocate("/e.bin", 9000), failed);
+    assert_eq!(fs.preallocate("/d.bin", 9000), failed);
+    assert_eq!(fs.free_clusters(), free);
+    assert_eq!(fs.recompute_free_count().ok(), free);
+    assert_eq!(fs.chain_length(fs.nth_cluster("/d.bin", 0).unwrap()), Ok(1));
+}
+
+#[test_case]
+fn names_are_compared_in_8_3_form() {
+    let mut fs = mount_empty();
+    fs.create_file("/readme").unwrap();
+    assert_eq!(fs.create_file("/readme."), Err(Error::AlreadyExists));
+    assert_eq!(fs.create_dir("/README"), Err(Error::AlreadyExists));
+    fs.write_file("/readme.", b"x").unwrap();
+    assert_eq!(fs.read_directory(2).unwrap().len(), 1);
+    assert_eq!(read_path(&fs, "/README").unwrap(), b"x");
+}
+
+struct TickingClock(AtomicU8);
+
+impl Clock for TickingClock {
+    fn now(&self) -> Timestamp {
+        Timestamp {
+            year: 2024,
+            month: 5,
+            day: 17,
+            hour: 13,
+            minute: self.0.fetch_add(1, Ordering::Relaxed),
+            second: 43,
+            millisecond: 250,
+        }
+    }
+}
+
+static CLOCK: TickingClock = TickingClock(AtomicU8::new(0));
+
+#[test_case]
+fn touch_creates_or_updates_modify_time() {
+    let disk = unmount(mount_empty());
+    let options = MountOptions {
+        clock: Some(&CLOCK),
+        ..MountOptions::default()
+    };
+    let mut fs = Fat32::mount_with_options(disk, options).unwrap();
+
+    fs.touch("/lock").unwrap();
+    let created = fs.raw_entry("/lock").unwrap();
+    let date = (2024 - 1980) << 9 | 5 << 5 | 17;
+    assert_eq!(u16::from_le_bytes([created[24], created[25]]), date);
+    assert_eq!(created[14..18], created[22..26]);
+    assert_eq!(u16::from_le_bytes([created[22], created[23]]) >> 11, 13);
+
+    fs.touch("/lock").unwrap();
+    let touched = fs.raw_entry("/lock").unwrap();
+    assert_eq!(touched[..22], created[..22]);
+    assert_eq!(touched[26..], created[26..]);
+    let minute = |raw: &[u8; 32]| (u16::from_le_bytes([raw[22], raw[23]]) >> 5) & 0x3F;
+    assert_eq!(minute(&touched), minute(&created) + 1);
+    assert_eq!(read_path(&fs, "/lock").unwrap(), b"");
+}
+
+#[test_case]
+fn builder_configures_mount() {
+    let mut fs = mount_empty();
+    fs.write_file("/keep.txt", b"keep").unwrap();
+    let disk = unmount(fs);
+
+    let mut fs = Fat32Builder::new().read_only(true).mount(disk).unwrap();
+    assert_eq!(fs.write_file("/new.txt", b"new"), Err(Error::ReadOnly));
+    assert_eq!(fs.delete_file("/keep.txt"), Err(Error::ReadOnly));
+    assert_eq!(read_path(&fs, "/keep.txt").unwrap(), b"keep");
+    let mut disk = unmount(fs);
+
+    let mut boot = [0u8; SECTOR_SIZE];
+    disk.read_sector(0, &mut boot).unwrap();
+    boot[48..50].copy_from_slice(&0u16.to_le_bytes());
+    disk.write_sector(0, &boot).unwrap();
+    let result = Fat32Builder::new().strict(true).mount(disk);
+    assert_eq!(result.err(), Some(Error::InvalidFat32Structure));
+}
+
+#[test_case]
+fn fat_cache_follows_writes() {
+    let disk = unmount(mount_empty());
+    let builder = Fat32Builder::new().fat_cache(true).max_path_depth(4);
+    assert!(builder.options().fat_cache);
+    let mut fs = builder.mount(disk).unwrap();
+
+    fs.write_file("/a.bin", &[1; 1500]).unwrap();
+    assert_eq!(read_path(&fs, "/a.bin").unwrap(), [1; 1500]);
+    fs.truncate_file("/a.bin", 100).unwrap();
+    fs.write_file("/b.bin", &[2; 1500]).unwrap();
+    assert_eq!(fs.verify_file("/a.bin"), Ok(()));
+    assert_eq!(fs.verify_file("/b.bin"), Ok(()));
+    assert_eq!(read_path(&fs, "/b.bin").unwrap(), [2; 1500]);
+    assert_eq!(
+        fs.find_entry("/a/b/c/d/e", 2).err(),
+        Some(Error::PathTooDeep)
+    );
+}
+
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    blog_os::test_panic_handler(info)
+}